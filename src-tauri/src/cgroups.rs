@@ -0,0 +1,376 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use sysinfo::Pid;
+
+use crate::procdetails::parse_cgroup;
+
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+// A cgroup directory that names something: a systemd unit or slice
+#[derive(Clone, Debug, PartialEq)]
+pub struct CgroupNode {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Container {
+    pub runtime: &'static str, // "docker", "podman" or "containerd"
+    pub id: String,
+    pub path: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CgroupMembership {
+    pub path: String,
+    pub unit: Option<CgroupNode>,  // innermost .service/.scope/... unit
+    pub slice: Option<CgroupNode>, // innermost .slice
+    pub container: Option<Container>,
+}
+
+const UNIT_SUFFIXES: [&str; 5] = [".service", ".scope", ".socket", ".mount", ".swap"];
+
+fn is_container_id(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// Container IDs appear as systemd scopes ("docker-<id>.scope", "libpod-<id>.scope",
+// "cri-containerd-<id>.scope") or, with the cgroupfs driver, as a bare directory
+// under "docker" or a kubepods hierarchy
+fn container_in(component: &str, ancestors: &[&str]) -> Option<(&'static str, String)> {
+    let name = component.strip_suffix(".scope").unwrap_or(component);
+    for (prefix, runtime) in [("docker-", "docker"), ("libpod-", "podman"), ("cri-containerd-", "containerd")] {
+        if let Some(id) = name.strip_prefix(prefix).filter(|id| is_container_id(id)) {
+            return Some((runtime, id.to_string()));
+        }
+    }
+    if is_container_id(name) {
+        match ancestors.last() {
+            Some(&"docker") => return Some(("docker", name.to_string())),
+            _ if ancestors.iter().any(|a| a.starts_with("kubepods")) => return Some(("containerd", name.to_string())),
+            _ => {}
+        }
+    }
+    None
+}
+
+// Splits a cgroup path into the systemd unit, slice and container it belongs to
+pub fn classify(path: &str) -> CgroupMembership {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    let prefix = |i: usize| format!("/{}", components[..=i].join("/"));
+    let mut membership = CgroupMembership {
+        path: path.to_string(),
+        unit: None,
+        slice: None,
+        container: None,
+    };
+
+    for (i, component) in components.iter().enumerate() {
+        let node = || CgroupNode {
+            name: component.to_string(),
+            path: prefix(i),
+        };
+        if component.ends_with(".slice") {
+            membership.slice = Some(node());
+        } else if UNIT_SUFFIXES.iter().any(|suffix| component.ends_with(suffix)) {
+            membership.unit = Some(node());
+        }
+        // The outermost match is the container; anything deeper is inside it
+        if membership.container.is_none() {
+            if let Some((runtime, id)) = container_in(component, &components[..i]) {
+                membership.container = Some(Container {
+                    runtime,
+                    id,
+                    path: prefix(i),
+                });
+            }
+        }
+    }
+    membership
+}
+
+// Uses the unified (v2) hierarchy, falling back to the named systemd hierarchy on v1 hosts
+pub fn membership(proc_root: &Path, pid: i32) -> Option<CgroupMembership> {
+    let text = fs::read_to_string(proc_root.join(pid.to_string()).join("cgroup")).ok()?;
+    let entries = parse_cgroup(&text);
+    let entry = entries
+        .iter()
+        .find(|e| e.hierarchy == 0 && e.controllers.is_empty())
+        .or_else(|| entries.iter().find(|e| e.controllers.iter().any(|c| c == "name=systemd")))?;
+    Some(classify(&entry.path))
+}
+
+// Memberships for every process, refreshed by the sampler alongside the process list
+pub struct CgroupTracker {
+    memberships: HashMap<Pid, CgroupMembership>,
+}
+
+impl CgroupTracker {
+    pub fn new() -> Self {
+        CgroupTracker {
+            memberships: HashMap::new(),
+        }
+    }
+
+    // Called with the sampler lock released
+    pub fn read(pids: &[Pid]) -> HashMap<Pid, CgroupMembership> {
+        #[cfg(target_os = "linux")]
+        {
+            use sysinfo::PidExt;
+            let proc_root = Path::new(crate::connections::PROC_ROOT);
+            pids.iter()
+                .filter_map(|pid| Some((*pid, membership(proc_root, pid.as_u32() as i32)?)))
+                .collect()
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = pids;
+            HashMap::new()
+        }
+    }
+
+    pub fn apply(&mut self, memberships: HashMap<Pid, CgroupMembership>) {
+        self.memberships = memberships;
+    }
+
+    pub fn membership_for(&self, pid: Pid) -> Option<&CgroupMembership> {
+        self.memberships.get(&pid)
+    }
+}
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct CgroupLimits {
+    pub memory_max: Option<u64>,    // bytes; None when unlimited
+    pub cpu_max_cores: Option<f64>, // quota / period; None when unlimited
+}
+
+// memory.max and cpu.max from a v2 cgroup directory. The root cgroup has neither.
+pub fn read_limits(cgroup_root: &Path, path: &str) -> CgroupLimits {
+    let dir = cgroup_root.join(path.trim_start_matches('/'));
+    let read = |file: &str| fs::read_to_string(dir.join(file)).ok();
+    CgroupLimits {
+        memory_max: read("memory.max").and_then(|s| s.trim().parse().ok()),
+        // "max 100000" when unlimited, "<quota> <period>" otherwise
+        cpu_max_cores: read("cpu.max").and_then(|s| {
+            let mut fields = s.split_whitespace();
+            let quota: f64 = fields.next()?.parse().ok()?;
+            let period: f64 = fields.next()?.parse().ok()?;
+            (period > 0.0).then(|| quota / period)
+        }),
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    Unit,
+    Slice,
+    Container,
+    Cgroup,
+}
+
+pub struct GroupMember {
+    pub pid: i32,
+    pub membership: CgroupMembership,
+    pub cpu: f32,
+    pub memory: u64, // PSS where readable, so shared pages are not counted per process
+    pub disk_read_bytes_per_sec: Option<f64>, // None when the process's counters are unreadable
+    pub disk_write_bytes_per_sec: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProcessGroup {
+    pub name: String, // unit, slice, container ID or cgroup path
+    pub cgroup_path: String, // groups are keyed by this; names repeat across parents
+    pub container_runtime: Option<&'static str>,
+    pub pids: Vec<i32>,
+    pub cpu: f32,
+    pub memory: u64, // bytes
+    pub disk_read_bytes_per_sec: f64, // over members whose counters are readable
+    pub disk_write_bytes_per_sec: f64,
+    pub limits: CgroupLimits,
+}
+
+// Processes outside any unit/slice/container (kernel threads in the root
+// cgroup, for instance) are left out when grouping by that kind
+pub fn group_processes(members: Vec<GroupMember>, by: GroupBy, cgroup_root: &Path) -> Vec<ProcessGroup> {
+    let mut groups: BTreeMap<String, ProcessGroup> = BTreeMap::new();
+    for member in members {
+        let m = &member.membership;
+        let (name, path, runtime) = match by {
+            GroupBy::Unit => match &m.unit {
+                Some(unit) => (unit.name.clone(), unit.path.clone(), None),
+                None => continue,
+            },
+            GroupBy::Slice => match &m.slice {
+                Some(slice) => (slice.name.clone(), slice.path.clone(), None),
+                None => continue,
+            },
+            GroupBy::Container => match &m.container {
+                Some(c) => (c.id.clone(), c.path.clone(), Some(c.runtime)),
+                None => continue,
+            },
+            GroupBy::Cgroup => (m.path.clone(), m.path.clone(), None),
+        };
+        // The same unit or slice name (dbus.service, app.slice) recurs under every
+        // user manager, so only the path identifies a group
+        let group = groups.entry(path.clone()).or_insert_with(|| ProcessGroup {
+            name,
+            limits: read_limits(cgroup_root, &path),
+            cgroup_path: path,
+            container_runtime: runtime,
+            pids: Vec::new(),
+            cpu: 0.0,
+            memory: 0,
+            disk_read_bytes_per_sec: 0.0,
+            disk_write_bytes_per_sec: 0.0,
+        });
+        group.pids.push(member.pid);
+        group.cpu += member.cpu;
+        group.memory += member.memory;
+        group.disk_read_bytes_per_sec += member.disk_read_bytes_per_sec.unwrap_or(0.0);
+        group.disk_write_bytes_per_sec += member.disk_write_bytes_per_sec.unwrap_or(0.0);
+    }
+
+    let mut groups: Vec<ProcessGroup> = groups.into_values().collect();
+    for group in &mut groups {
+        group.pids.sort_unstable();
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCKER_ID: &str = "3f4e2a1b9c8d7e6f5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f";
+
+    fn fixture_root() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sys_fs_cgroup")
+    }
+
+    #[test]
+    fn classifies_systemd_units_and_slices() {
+        let m = classify("/user.slice/user-1000.slice/user@1000.service/app.slice/app-org.mozilla.firefox-1234.scope");
+        let unit = m.unit.unwrap();
+        assert_eq!(unit.name, "app-org.mozilla.firefox-1234.scope");
+        assert_eq!(m.slice.unwrap().path, "/user.slice/user-1000.slice/user@1000.service/app.slice");
+        assert_eq!(m.container, None);
+
+        let root = classify("/");
+        assert_eq!(root.unit, None);
+        assert_eq!(root.slice, None);
+    }
+
+    #[test]
+    fn detects_containers_from_each_runtime() {
+        let docker = classify(&format!("/system.slice/docker-{}.scope", DOCKER_ID));
+        let c = docker.container.unwrap();
+        assert_eq!((c.runtime, c.id.as_str()), ("docker", DOCKER_ID));
+        assert_eq!(docker.unit.unwrap().name, format!("docker-{}.scope", DOCKER_ID));
+
+        let cgroupfs = classify(&format!("/docker/{}", DOCKER_ID));
+        assert_eq!(cgroupfs.container.unwrap().path, format!("/docker/{}", DOCKER_ID));
+
+        let podman = classify(&format!("/machine.slice/libpod-{}.scope/container", DOCKER_ID));
+        let c = podman.container.unwrap();
+        assert_eq!(c.runtime, "podman");
+        assert_eq!(c.path, format!("/machine.slice/libpod-{}.scope", DOCKER_ID));
+        // conmon monitors the container but runs outside it
+        assert_eq!(classify(&format!("/machine.slice/libpod-conmon-{}.scope", DOCKER_ID)).container, None);
+
+        let k8s = classify(&format!(
+            "/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod12ab.slice/cri-containerd-{}.scope",
+            DOCKER_ID
+        ));
+        assert_eq!(k8s.container.unwrap().runtime, "containerd");
+        assert_eq!(classify(&format!("/kubepods/besteffort/pod12ab/{}", DOCKER_ID)).container.unwrap().runtime, "containerd");
+    }
+
+    #[test]
+    fn reads_memory_and_cpu_limits() {
+        let limits = read_limits(&fixture_root(), &format!("/system.slice/docker-{}.scope", DOCKER_ID));
+        assert_eq!(limits.memory_max, Some(536_870_912));
+        assert_eq!(limits.cpu_max_cores, Some(1.5));
+
+        let unlimited = read_limits(&fixture_root(), "/user.slice");
+        assert_eq!(unlimited, CgroupLimits::default());
+    }
+
+    #[test]
+    fn aggregates_members_per_group() {
+        let member = |pid: i32, path: &str, cpu: f32, memory: u64| GroupMember {
+            pid,
+            membership: classify(path),
+            cpu,
+            memory,
+            disk_read_bytes_per_sec: Some(100.0),
+            disk_write_bytes_per_sec: (pid != 10).then_some(10.0),
+        };
+        let scope = format!("/system.slice/docker-{}.scope", DOCKER_ID);
+        let members = || {
+            vec![
+                member(30, &scope, 20.0, 300),
+                member(10, &scope, 5.0, 100),
+                member(20, "/system.slice/sshd.service", 1.0, 50),
+                member(2, "/", 0.0, 0),
+            ]
+        };
+
+        let units = group_processes(members(), GroupBy::Unit, &fixture_root());
+        assert_eq!(units.len(), 2);
+        let docker = &units[0];
+        assert_eq!(docker.pids, vec![10, 30]);
+        assert_eq!(docker.cpu, 25.0);
+        assert_eq!(docker.memory, 400);
+        assert_eq!(docker.disk_read_bytes_per_sec, 200.0);
+        // pid 10's counters were unreadable
+        assert_eq!(docker.disk_write_bytes_per_sec, 10.0);
+        assert_eq!(docker.limits.memory_max, Some(536_870_912));
+        assert_eq!(units[1].name, "sshd.service");
+
+        let containers = group_processes(members(), GroupBy::Container, &fixture_root());
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].name, DOCKER_ID);
+        assert_eq!(containers[0].container_runtime, Some("docker"));
+
+        let cgroups = group_processes(members(), GroupBy::Cgroup, &fixture_root());
+        assert_eq!(cgroups.len(), 3);
+        assert_eq!(cgroups[0].name, "/");
+    }
+
+    #[test]
+    fn same_named_units_under_different_parents_stay_apart() {
+        let member = |pid: i32, path: &str| GroupMember {
+            pid,
+            membership: classify(path),
+            cpu: 1.0,
+            memory: 100,
+            disk_read_bytes_per_sec: None,
+            disk_write_bytes_per_sec: None,
+        };
+        let members = vec![
+            member(10, "/system.slice/dbus.service"),
+            member(20, "/user.slice/user-1000.slice/user@1000.service/session.slice/dbus.service"),
+            member(30, "/user.slice/user-1001.slice/user@1001.service/session.slice/dbus.service"),
+        ];
+
+        let units = group_processes(members, GroupBy::Unit, &fixture_root());
+        assert_eq!(units.len(), 3);
+        assert!(units.iter().all(|g| g.name == "dbus.service" && g.pids.len() == 1));
+        assert_eq!(units[0].cgroup_path, "/system.slice/dbus.service");
+
+        let slices = group_processes(
+            vec![
+                member(20, "/user.slice/user-1000.slice/user@1000.service/app.slice/a.scope"),
+                member(30, "/user.slice/user-1001.slice/user@1001.service/app.slice/b.scope"),
+            ],
+            GroupBy::Slice,
+            &fixture_root(),
+        );
+        assert_eq!(slices.len(), 2);
+        assert_eq!(slices[1].cgroup_path, "/user.slice/user-1001.slice/user@1001.service/app.slice");
+    }
+}
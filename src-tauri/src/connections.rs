@@ -0,0 +1,303 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+pub const PROC_ROOT: &str = "/proc";
+
+// One socket from /proc/net, with the process that holds it open if known
+#[derive(Serialize, Clone, Debug)]
+pub struct Connection {
+    pub protocol: String, // "tcp", "tcp6", "udp", "udp6" or "unix"
+    pub local_address: Option<String>,
+    pub local_port: Option<u16>,
+    pub remote_address: Option<String>, // None while listening or unconnected
+    pub remote_port: Option<u16>,
+    pub path: Option<String>, // unix sockets only; abstract names start with '@'
+    pub socket_type: Option<String>, // unix sockets only: "stream", "dgram" or "seqpacket"
+    pub state: String,
+    pub uid: Option<u32>,
+    pub inode: u64,
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+}
+
+// The kernel prints each 32-bit word of the address with %08X on the raw
+// network-order bytes, so the native byte order of the parsed word is the address
+fn parse_ipv4(hex: &str) -> Option<Ipv4Addr> {
+    let word = u32::from_str_radix(hex, 16).ok()?;
+    Some(Ipv4Addr::from(word.to_ne_bytes()))
+}
+
+fn parse_ipv6(hex: &str) -> Option<Ipv6Addr> {
+    if hex.len() != 32 {
+        return None;
+    }
+    let mut bytes = [0u8; 16];
+    for (i, chunk) in bytes.chunks_mut(4).enumerate() {
+        let word = u32::from_str_radix(&hex[i * 8..i * 8 + 8], 16).ok()?;
+        chunk.copy_from_slice(&word.to_ne_bytes());
+    }
+    Some(Ipv6Addr::from(bytes))
+}
+
+// "0100007F:0277" -> ("127.0.0.1", 631)
+fn parse_endpoint(field: &str) -> Option<(String, u16)> {
+    let (addr, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let addr = match addr.len() {
+        8 => parse_ipv4(addr)?.to_string(),
+        _ => parse_ipv6(addr)?.to_string(),
+    };
+    Some((addr, port))
+}
+
+// TCP states from include/net/tcp_states.h
+fn tcp_state(code: u8) -> &'static str {
+    match code {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        0x0C => "NEW_SYN_RECV",
+        _ => "UNKNOWN",
+    }
+}
+
+// Parses /proc/net/{tcp,tcp6,udp,udp6}; `protocol` is the file name
+pub fn parse_inet(text: &str, protocol: &str) -> Vec<Connection> {
+    let is_udp = protocol.starts_with("udp");
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                return None;
+            }
+            let (local_address, local_port) = parse_endpoint(fields[1])?;
+            let (remote_address, remote_port) = parse_endpoint(fields[2])?;
+            let code = u8::from_str_radix(fields[3], 16).ok()?;
+            // UDP reuses the TCP codes: 07 is an unconnected socket, 01 a connected one
+            let state = match (is_udp, code) {
+                (true, 0x07) => "UNCONN",
+                _ => tcp_state(code),
+            };
+            let remote_is_wildcard =
+                remote_port == 0 && (remote_address == "0.0.0.0" || remote_address == "::");
+
+            Some(Connection {
+                protocol: protocol.to_string(),
+                local_address: Some(local_address),
+                local_port: Some(local_port),
+                remote_address: (!remote_is_wildcard).then_some(remote_address),
+                remote_port: (!remote_is_wildcard).then_some(remote_port),
+                path: None,
+                socket_type: None,
+                state: state.to_string(),
+                uid: fields[7].parse().ok(),
+                inode: fields[9].parse().ok()?,
+                pid: None,
+                process_name: None,
+            })
+        })
+        .collect()
+}
+
+// __SO_ACCEPTCON in the Flags column marks a listening socket
+const UNIX_ACCEPTCON: u32 = 0x0001_0000;
+
+// Parses /proc/net/unix
+pub fn parse_unix(text: &str) -> Vec<Connection> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 7 {
+                return None;
+            }
+            let flags = u32::from_str_radix(fields[3], 16).ok()?;
+            let socket_type = match fields[4] {
+                "0001" => "stream",
+                "0002" => "dgram",
+                "0005" => "seqpacket",
+                _ => "unknown",
+            };
+            let state = if flags & UNIX_ACCEPTCON != 0 {
+                "LISTEN"
+            } else {
+                match fields[5] {
+                    "01" => "UNCONNECTED",
+                    "02" => "CONNECTING",
+                    "03" => "CONNECTED",
+                    "04" => "DISCONNECTING",
+                    _ => "UNKNOWN",
+                }
+            };
+
+            Some(Connection {
+                protocol: "unix".to_string(),
+                local_address: None,
+                local_port: None,
+                remote_address: None,
+                remote_port: None,
+                // Paths may contain spaces, so take everything after the inode column
+                path: (fields.len() > 7).then(|| fields[7..].join(" ")),
+                socket_type: Some(socket_type.to_string()),
+                state: state.to_string(),
+                uid: None,
+                inode: fields[6].parse().ok()?,
+                pid: None,
+                process_name: None,
+            })
+        })
+        .collect()
+}
+
+// Socket inode -> owning pid, from the "socket:[12345]" links in /proc/<pid>/fd.
+// Other users' processes are unreadable without privileges and are skipped.
+pub fn socket_owners(proc_root: &Path) -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+    let Ok(entries) = fs::read_dir(proc_root) else {
+        return owners;
+    };
+
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            if let Some(inode) = target
+                .strip_prefix("socket:[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|n| n.parse::<u64>().ok())
+            {
+                // A socket shared across fork() is attributed to the lowest pid
+                let owner = owners.entry(inode).or_insert(pid);
+                *owner = (*owner).min(pid);
+            }
+        }
+    }
+    owners
+}
+
+// Every socket in the current network namespace, tagged with its owning process
+pub fn list_connections(proc_root: &Path) -> Vec<Connection> {
+    let net = proc_root.join("net");
+    let mut connections = Vec::new();
+    for protocol in ["tcp", "tcp6", "udp", "udp6"] {
+        if let Ok(text) = fs::read_to_string(net.join(protocol)) {
+            connections.extend(parse_inet(&text, protocol));
+        }
+    }
+    if let Ok(text) = fs::read_to_string(net.join("unix")) {
+        connections.extend(parse_unix(&text));
+    }
+
+    let owners = socket_owners(proc_root);
+    let mut names: HashMap<u32, Option<String>> = HashMap::new();
+    for conn in &mut connections {
+        // TIME_WAIT sockets have no inode; they belong to no process any more
+        let Some(&pid) = owners.get(&conn.inode).filter(|_| conn.inode != 0) else {
+            continue;
+        };
+        conn.pid = Some(pid);
+        conn.process_name = names
+            .entry(pid)
+            .or_insert_with(|| {
+                fs::read_to_string(proc_root.join(pid.to_string()).join("comm"))
+                    .ok()
+                    .map(|c| c.trim().to_string())
+            })
+            .clone();
+    }
+    connections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proc_net").join(name)).unwrap()
+    }
+
+    #[test]
+    fn parses_tcp_listeners_and_established() {
+        let conns = parse_inet(&fixture("tcp"), "tcp");
+        assert_eq!(conns.len(), 4);
+
+        let cups = &conns[0];
+        assert_eq!(cups.local_address.as_deref(), Some("127.0.0.1"));
+        assert_eq!(cups.local_port, Some(631));
+        assert_eq!(cups.remote_address, None);
+        assert_eq!(cups.state, "LISTEN");
+        assert_eq!(cups.inode, 21874);
+
+        let https = &conns[2];
+        assert_eq!(https.local_address.as_deref(), Some("192.168.1.15"));
+        assert_eq!(https.remote_address.as_deref(), Some("172.217.48.34"));
+        assert_eq!(https.remote_port, Some(443));
+        assert_eq!(https.state, "ESTABLISHED");
+        assert_eq!(https.uid, Some(1000));
+
+        assert_eq!(conns[3].state, "TIME_WAIT");
+        assert_eq!(conns[3].inode, 0);
+    }
+
+    #[test]
+    fn parses_ipv6_including_mapped_addresses() {
+        let conns = parse_inet(&fixture("tcp6"), "tcp6");
+        assert_eq!(conns.len(), 3);
+        assert_eq!(conns[0].local_address.as_deref(), Some("::"));
+        assert_eq!(conns[0].local_port, Some(8080));
+        assert_eq!(conns[1].local_address.as_deref(), Some("::1"));
+        assert_eq!(conns[1].remote_port, Some(46310));
+        assert_eq!(conns[2].remote_address.as_deref(), Some("::ffff:192.168.1.42"));
+        assert_eq!(conns[2].state, "CLOSE_WAIT");
+    }
+
+    #[test]
+    fn parses_udp_states() {
+        let conns = parse_inet(&fixture("udp"), "udp");
+        assert_eq!(conns.len(), 3);
+        assert_eq!(conns[1].local_address.as_deref(), Some("127.0.0.53"));
+        assert_eq!(conns[1].local_port, Some(53));
+        assert_eq!(conns[1].state, "UNCONN");
+        assert_eq!(conns[2].remote_address.as_deref(), Some("8.8.8.8"));
+        assert_eq!(conns[2].state, "ESTABLISHED");
+
+        let conns6 = parse_inet(&fixture("udp6"), "udp6");
+        assert_eq!(conns6[1].local_address.as_deref(), Some("fe80::e2c:1bff:fe4d:5e6f"));
+        assert_eq!(conns6[1].local_port, Some(546));
+    }
+
+    #[test]
+    fn parses_unix_sockets() {
+        let conns = parse_unix(&fixture("unix"));
+        assert_eq!(conns.len(), 6);
+        assert_eq!(conns[0].path.as_deref(), Some("/run/systemd/private"));
+        assert_eq!(conns[0].state, "LISTEN");
+        assert_eq!(conns[1].path.as_deref(), Some("@/tmp/.X11-unix/X0"));
+        assert_eq!(conns[2].state, "CONNECTED");
+        assert_eq!(conns[3].path, None);
+        assert_eq!(conns[4].socket_type.as_deref(), Some("dgram"));
+        assert_eq!(conns[4].state, "UNCONNECTED");
+        assert_eq!(conns[5].socket_type.as_deref(), Some("seqpacket"));
+        assert_eq!(conns[5].inode, 15009);
+    }
+}
@@ -0,0 +1,265 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub const SYS_BLOCK_ROOT: &str = "/sys/block";
+
+// /proc/diskstats always counts in 512-byte sectors, whatever the device's block size
+const SECTOR_SIZE: u64 = 512;
+
+// Cumulative counters for one block device, as found in /proc/diskstats
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiskStat {
+    pub device: String,
+    pub reads: u64,
+    pub sectors_read: u64,
+    pub read_ms: u64,
+    pub writes: u64,
+    pub sectors_written: u64,
+    pub write_ms: u64,
+    pub io_ms: u64,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct DiskIoRate {
+    pub device: String,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    pub read_iops: f64,
+    pub write_iops: f64,
+    pub avg_read_latency_ms: f64,
+    pub avg_write_latency_ms: f64,
+    pub utilization: f64, // percent of wall time the device had I/O in flight
+}
+
+// Accepts both the 14-field (pre-4.18) and the newer 18/20-field layouts;
+// the extra discard/flush columns are not needed here
+pub fn parse_diskstats(text: &str) -> Vec<DiskStat> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 14 {
+                return None;
+            }
+            let num = |i: usize| fields[i].parse::<u64>().ok();
+            Some(DiskStat {
+                device: fields[2].to_string(),
+                reads: num(3)?,
+                sectors_read: num(5)?,
+                read_ms: num(6)?,
+                writes: num(7)?,
+                sectors_written: num(9)?,
+                write_ms: num(10)?,
+                io_ms: num(12)?,
+            })
+        })
+        .collect()
+}
+
+// Counters can reset when a device is re-attached; a negative delta is treated as idle
+pub fn compute_rate(prev: &DiskStat, cur: &DiskStat, elapsed: Duration) -> DiskIoRate {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return DiskIoRate {
+            device: cur.device.clone(),
+            ..Default::default()
+        };
+    }
+
+    let reads = cur.reads.saturating_sub(prev.reads);
+    let writes = cur.writes.saturating_sub(prev.writes);
+    let read_ms = cur.read_ms.saturating_sub(prev.read_ms);
+    let write_ms = cur.write_ms.saturating_sub(prev.write_ms);
+    let io_ms = cur.io_ms.saturating_sub(prev.io_ms);
+    let latency = |ms: u64, ops: u64| if ops > 0 { ms as f64 / ops as f64 } else { 0.0 };
+
+    DiskIoRate {
+        device: cur.device.clone(),
+        read_bytes_per_sec: (cur.sectors_read.saturating_sub(prev.sectors_read) * SECTOR_SIZE) as f64 / secs,
+        write_bytes_per_sec: (cur.sectors_written.saturating_sub(prev.sectors_written) * SECTOR_SIZE) as f64 / secs,
+        read_iops: reads as f64 / secs,
+        write_iops: writes as f64 / secs,
+        avg_read_latency_ms: latency(read_ms, reads),
+        avg_write_latency_ms: latency(write_ms, writes),
+        utilization: (io_ms as f64 / (secs * 1000.0) * 100.0).min(100.0),
+    }
+}
+
+// Device-mapper volumes (LVM, LUKS) are mounted as /dev/mapper/<name> but
+// counted in /proc/diskstats as dm-N; <sys_block>/dm-N/dm/name links the two
+pub fn read_mapper_names(sys_block: &Path) -> HashMap<String, String> {
+    let Ok(entries) = fs::read_dir(sys_block) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let device = entry.file_name().to_string_lossy().into_owned();
+            if !device.starts_with("dm-") {
+                return None;
+            }
+            let name = fs::read_to_string(entry.path().join("dm/name")).ok()?;
+            Some((name.trim().to_string(), device))
+        })
+        .collect()
+}
+
+// Keeps the previous /proc/diskstats sample so rates can be derived on each refresh
+pub struct DiskIoTracker {
+    prev: HashMap<String, DiskStat>,
+    prev_at: Option<Instant>,
+    rates: Vec<DiskIoRate>,
+    mapper: HashMap<String, String>, // mapper name -> dm-N
+}
+
+impl DiskIoTracker {
+    pub fn new() -> Self {
+        DiskIoTracker {
+            prev: HashMap::new(),
+            prev_at: None,
+            rates: Vec::new(),
+            mapper: HashMap::new(),
+        }
+    }
+
+    pub fn update(&mut self) {
+        let Some(text) = read_diskstats() else {
+            return;
+        };
+        self.mapper = read_mapper_names(Path::new(SYS_BLOCK_ROOT));
+        self.update_from(&parse_diskstats(&text), Instant::now());
+    }
+
+    fn update_from(&mut self, stats: &[DiskStat], now: Instant) {
+        let elapsed = self.prev_at.map(|t| now.duration_since(t));
+        self.rates = stats
+            .iter()
+            .filter_map(|cur| {
+                let prev = self.prev.get(&cur.device)?;
+                Some(compute_rate(prev, cur, elapsed?))
+            })
+            .collect();
+        self.prev = stats.iter().map(|s| (s.device.clone(), s.clone())).collect();
+        self.prev_at = Some(now);
+    }
+
+    pub fn rates(&self) -> &[DiskIoRate] {
+        &self.rates
+    }
+
+    // DiskInfo names are device paths such as "/dev/nvme0n1p2" or "/dev/mapper/vg0-root"
+    pub fn rate_for(&self, disk_name: &str) -> Option<&DiskIoRate> {
+        let device = match disk_name.strip_prefix("/dev/mapper/") {
+            Some(name) => self.mapper.get(name)?.as_str(),
+            None => disk_name.rsplit('/').next().unwrap_or(disk_name),
+        };
+        self.rates.iter().find(|r| r.device == device)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_diskstats() -> Option<String> {
+    std::fs::read_to_string("/proc/diskstats").ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_diskstats() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = include_str!("../tests/fixtures/diskstats_before");
+    const AFTER: &str = include_str!("../tests/fixtures/diskstats_after");
+
+    fn find<'a>(stats: &'a [DiskStat], device: &str) -> &'a DiskStat {
+        stats.iter().find(|s| s.device == device).unwrap()
+    }
+
+    #[test]
+    fn parses_modern_and_legacy_layouts() {
+        let stats = parse_diskstats(BEFORE);
+        assert_eq!(stats.len(), 7);
+
+        let nvme = find(&stats, "nvme0n1");
+        assert_eq!(nvme.reads, 152340);
+        assert_eq!(nvme.sectors_read, 9876544);
+        assert_eq!(nvme.write_ms, 234560);
+        assert_eq!(nvme.io_ms, 123450);
+
+        // sda uses the 14-field layout of older kernels
+        let sda = find(&stats, "sda");
+        assert_eq!(sda.writes, 2000);
+        assert_eq!(sda.io_ms, 15000);
+    }
+
+    #[test]
+    fn skips_short_and_malformed_lines() {
+        let stats = parse_diskstats("8 0 sda 1 2 3\n8 0 sdb x 0 0 0 0 0 0 0 0 0 0\n");
+        assert!(stats.is_empty());
+    }
+
+    #[test]
+    fn computes_rates_between_samples() {
+        let before = parse_diskstats(BEFORE);
+        let after = parse_diskstats(AFTER);
+        let rate = compute_rate(
+            find(&before, "nvme0n1"),
+            find(&after, "nvme0n1"),
+            Duration::from_secs(1),
+        );
+
+        assert_eq!(rate.read_bytes_per_sec, 2.0 * 1024.0 * 1024.0);
+        assert_eq!(rate.write_bytes_per_sec, 1024.0 * 1024.0);
+        assert_eq!(rate.read_iops, 200.0);
+        assert_eq!(rate.write_iops, 50.0);
+        assert_eq!(rate.avg_read_latency_ms, 0.5);
+        assert_eq!(rate.avg_write_latency_ms, 3.0);
+        assert_eq!(rate.utilization, 25.0);
+    }
+
+    #[test]
+    fn counter_reset_reads_as_idle() {
+        let before = parse_diskstats(AFTER);
+        let after = parse_diskstats(BEFORE);
+        let rate = compute_rate(
+            find(&before, "nvme0n1"),
+            find(&after, "nvme0n1"),
+            Duration::from_secs(1),
+        );
+        assert_eq!(rate.read_bytes_per_sec, 0.0);
+        assert_eq!(rate.utilization, 0.0);
+    }
+
+    #[test]
+    fn reads_device_mapper_names() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs/block");
+        let names = read_mapper_names(&root);
+        assert_eq!(names.len(), 2);
+        assert_eq!(names["vg0-root"], "dm-0");
+        assert_eq!(names["cryptdata"], "dm-1");
+        assert!(read_mapper_names(Path::new("/nonexistent")).is_empty());
+    }
+
+    #[test]
+    fn tracker_matches_disk_names_to_devices() {
+        let start = Instant::now();
+        let mut tracker = DiskIoTracker::new();
+        tracker.mapper = read_mapper_names(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs/block"));
+        tracker.update_from(&parse_diskstats(BEFORE), start);
+        assert!(tracker.rates().is_empty());
+
+        tracker.update_from(&parse_diskstats(AFTER), start + Duration::from_secs(2));
+        let part = tracker.rate_for("/dev/nvme0n1p2").unwrap();
+        assert_eq!(part.read_iops, 100.0);
+        assert_eq!(tracker.rate_for("/dev/sda1").unwrap().write_bytes_per_sec, 0.0);
+        // LVM/LUKS volumes resolve to their dm-N device
+        assert_eq!(tracker.rate_for("/dev/mapper/vg0-root").unwrap().read_bytes_per_sec, 2.0 * 1024.0 * 1024.0);
+        assert_eq!(tracker.rate_for("/dev/dm-0").unwrap().device, "dm-0");
+        assert!(tracker.rate_for("/dev/mapper/missing").is_none());
+    }
+}
@@ -0,0 +1,236 @@
+use std::fs;
+use std::path::Path;
+
+pub const DRM_ROOT: &str = "/sys/class/drm";
+
+// A PCI display controller with a DRM card node
+#[derive(Debug)]
+pub struct DrmCard {
+    pub pci_slot: String, // e.g. "0000:03:00.0"
+    pub vendor_id: u16,
+    pub device_id: u16,
+    pub driver: Option<String>,
+    pub stats: DrmStats,
+}
+
+// Live statistics for one /sys/class/drm/cardN, as far as its driver exposes them
+#[derive(Default, Debug)]
+pub struct DrmStats {
+    pub utilization: Option<f32>, // percent
+    pub vram_total: Option<u64>,  // bytes
+    pub vram_used: Option<u64>,   // bytes
+    pub core_clock_mhz: Option<u64>,
+    pub max_core_clock_mhz: Option<u64>,
+    pub memory_clock_mhz: Option<u64>,
+    pub temperature: Option<f32>, // °C
+}
+
+fn read_hex_u16(path: &Path) -> Option<u16> {
+    let text = fs::read_to_string(path).ok()?;
+    u16::from_str_radix(text.trim().trim_start_matches("0x"), 16).ok()
+}
+
+fn link_name(path: &Path) -> Option<String> {
+    let target = fs::read_link(path).ok()?;
+    Some(target.file_name()?.to_string_lossy().into_owned())
+}
+
+// One entry per PCI device, so connector nodes (card0-DP-1) and render nodes are
+// skipped, as are non-PCI framebuffers such as simpledrm
+pub fn discover_cards(drm_root: &Path) -> Vec<DrmCard> {
+    let mut cards: Vec<DrmCard> = Vec::new();
+    let Ok(entries) = fs::read_dir(drm_root) else {
+        return cards;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.starts_with("card") || name.contains('-') {
+            continue;
+        }
+        let card_dir = entry.path();
+        let Ok(device_dir) = fs::canonicalize(card_dir.join("device")) else {
+            continue;
+        };
+        let (Some(vendor_id), Some(device_id)) = (
+            read_hex_u16(&device_dir.join("vendor")),
+            read_hex_u16(&device_dir.join("device")),
+        ) else {
+            continue;
+        };
+        let pci_slot = device_dir.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        if cards.iter().any(|c| c.pci_slot == pci_slot) {
+            continue;
+        }
+
+        cards.push(DrmCard {
+            pci_slot,
+            vendor_id,
+            device_id,
+            driver: link_name(&device_dir.join("driver")),
+            stats: read_card_stats(&card_dir),
+        });
+    }
+
+    cards.sort_by(|a, b| a.pci_slot.cmp(&b.pci_slot));
+    cards
+}
+
+// Out-of-tree modules (nvidia) carry their own version; in-tree drivers ship with the kernel
+pub fn driver_version(driver: &str) -> Option<String> {
+    fs::read_to_string(format!("/sys/module/{}/version", driver))
+        .or_else(|_| fs::read_to_string("/proc/sys/kernel/osrelease"))
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse::<u64>().ok()
+}
+
+fn read_card_stats(card_dir: &Path) -> DrmStats {
+    let device = card_dir.join("device");
+    let mut stats = DrmStats {
+        // amdgpu exposes these directly; other drivers simply lack the files
+        utilization: read_u64(&device.join("gpu_busy_percent")).map(|p| p as f32),
+        vram_total: read_u64(&device.join("mem_info_vram_total")),
+        vram_used: read_u64(&device.join("mem_info_vram_used")),
+        temperature: read_hwmon_temperature(&device),
+        ..Default::default()
+    };
+
+    // amdgpu: DPM tables list every level with the active one marked '*'
+    if let Ok(sclk) = fs::read_to_string(device.join("pp_dpm_sclk")) {
+        let (current, max) = parse_dpm_levels(&sclk);
+        stats.core_clock_mhz = current;
+        stats.max_core_clock_mhz = max;
+    }
+    if let Ok(mclk) = fs::read_to_string(device.join("pp_dpm_mclk")) {
+        stats.memory_clock_mhz = parse_dpm_levels(&mclk).0;
+    }
+
+    // i915: frequency counters live on the card itself; "act" is 0 while the GT is in RC6
+    if stats.core_clock_mhz.is_none() {
+        if let Some(cur) = read_u64(&card_dir.join("gt_cur_freq_mhz")) {
+            stats.core_clock_mhz = read_u64(&card_dir.join("gt_act_freq_mhz"))
+                .filter(|&f| f > 0)
+                .or(Some(cur));
+            stats.max_core_clock_mhz = read_u64(&card_dir.join("gt_RP0_freq_mhz"))
+                .or_else(|| read_u64(&card_dir.join("gt_max_freq_mhz")));
+        }
+    }
+
+    // xe: per-GT frequency directories under the PCI device
+    if stats.core_clock_mhz.is_none() {
+        let freq = device.join("tile0/gt0/freq0");
+        if let Some(cur) = read_u64(&freq.join("cur_freq")) {
+            stats.core_clock_mhz = read_u64(&freq.join("act_freq"))
+                .filter(|&f| f > 0)
+                .or(Some(cur));
+            stats.max_core_clock_mhz = read_u64(&freq.join("rp0_freq"))
+                .or_else(|| read_u64(&freq.join("max_freq")));
+        }
+    }
+
+    stats
+}
+
+// GPU hwmon chips report the edge/package temperature as temp1
+fn read_hwmon_temperature(device: &Path) -> Option<f32> {
+    let entries = fs::read_dir(device.join("hwmon")).ok()?;
+    entries
+        .flatten()
+        .find_map(|e| read_u64(&e.path().join("temp1_input")))
+        .map(|milli| milli as f32 / 1000.0)
+}
+
+// Returns (active level, highest level) in MHz from a pp_dpm_* table such as
+// "0: 500Mhz\n1: 1800Mhz *"
+fn parse_dpm_levels(text: &str) -> (Option<u64>, Option<u64>) {
+    let mut current = None;
+    let mut max = None;
+    for line in text.lines() {
+        let Some((_, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let Some(mhz) = rest
+            .split_whitespace()
+            .next()
+            .and_then(|v| v.to_lowercase().trim_end_matches("mhz").parse::<u64>().ok())
+        else {
+            continue;
+        };
+        max = max.max(Some(mhz));
+        if line.trim_end().ends_with('*') {
+            current = Some(mhz);
+        }
+    }
+    (current, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs/class/drm")
+    }
+
+    fn card<'a>(cards: &'a [DrmCard], slot: &str) -> &'a DrmCard {
+        cards.iter().find(|c| c.pci_slot == slot).unwrap()
+    }
+
+    #[test]
+    fn parses_dpm_levels() {
+        assert_eq!(parse_dpm_levels("0: 500Mhz\n1: 1500Mhz *\n2: 2615Mhz\n"), (Some(1500), Some(2615)));
+        // Older kernels print "MHz"; some tables have no active level
+        assert_eq!(parse_dpm_levels("0: 300MHz\n1: 900MHz\n"), (None, Some(900)));
+        assert_eq!(parse_dpm_levels("S: 19Mhz *\nnot a level\n"), (Some(19), Some(19)));
+        assert_eq!(parse_dpm_levels(""), (None, None));
+    }
+
+    #[test]
+    fn discovers_one_card_per_pci_device() {
+        let cards = discover_cards(&fixture_root());
+        // Connector and render nodes, the duplicate card4 and the platform framebuffer are skipped
+        let slots: Vec<&str> = cards.iter().map(|c| c.pci_slot.as_str()).collect();
+        assert_eq!(slots, vec!["0000:00:02.0", "0000:00:04.0", "0000:03:00.0"]);
+
+        let amd = card(&cards, "0000:03:00.0");
+        assert_eq!((amd.vendor_id, amd.device_id), (0x1002, 0x73bf));
+        assert_eq!(amd.driver.as_deref(), Some("amdgpu"));
+    }
+
+    #[test]
+    fn reads_amdgpu_stats() {
+        let cards = discover_cards(&fixture_root());
+        let stats = &card(&cards, "0000:03:00.0").stats;
+        assert_eq!(stats.utilization, Some(37.0));
+        assert_eq!(stats.vram_total, Some(17_163_091_968));
+        assert_eq!(stats.vram_used, Some(1_342_177_280));
+        assert_eq!(stats.core_clock_mhz, Some(1500));
+        assert_eq!(stats.max_core_clock_mhz, Some(2615));
+        assert_eq!(stats.memory_clock_mhz, Some(1000));
+        assert_eq!(stats.temperature, Some(48.0));
+    }
+
+    #[test]
+    fn falls_back_to_i915_and_xe_frequencies() {
+        let cards = discover_cards(&fixture_root());
+
+        // gt_act_freq_mhz reads 0 in RC6, so the requested frequency is used
+        let i915 = card(&cards, "0000:00:02.0");
+        assert_eq!(i915.driver.as_deref(), Some("i915"));
+        assert_eq!(i915.stats.core_clock_mhz, Some(1100));
+        assert_eq!(i915.stats.max_core_clock_mhz, Some(1450));
+        assert_eq!(i915.stats.utilization, None);
+
+        let xe = card(&cards, "0000:00:04.0");
+        assert_eq!(xe.driver.as_deref(), Some("xe"));
+        assert_eq!(xe.stats.core_clock_mhz, Some(800));
+        // No rp0_freq, so max_freq stands in
+        assert_eq!(xe.stats.max_core_clock_mhz, Some(2400));
+    }
+}
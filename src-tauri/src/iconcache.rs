@@ -0,0 +1,201 @@
+use base64::engine::general_purpose;
+use base64::Engine;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Files kept on disk; every executable update adds one, so the least recently
+// used are removed past this
+const MAX_DISK_ENTRIES: usize = 1000;
+
+// Identifies one build of an executable: the key changes when the file is
+// replaced, so updated apps pick up their new icon. FNV-1a rather than
+// DefaultHasher because keys name files on disk and must be stable across runs.
+pub fn cache_key(exe: &Path) -> Option<String> {
+    let modified = fs::metadata(exe).ok()?.modified().ok()?;
+    let mtime = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in exe.to_string_lossy().bytes().chain(mtime.to_le_bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    Some(format!("{:016x}", hash))
+}
+
+// Base64 PNGs by cache key, backed by <dir>/<key>.png. Executables without an
+// icon are remembered too, as an empty file.
+pub struct IconCache {
+    memory: HashMap<String, Option<String>>,
+    dir: Option<PathBuf>,
+}
+
+pub type IconCacheState = Arc<Mutex<IconCache>>;
+
+impl IconCache {
+    pub fn new() -> Self {
+        IconCache {
+            memory: HashMap::new(),
+            dir: None,
+        }
+    }
+
+    pub fn set_dir(&mut self, dir: PathBuf) {
+        self.dir = Some(dir);
+    }
+}
+
+// Memory, then disk, then `extract`. The lock is released while reading and
+// extracting so one slow icon does not hold up the rest.
+pub fn lookup(state: &IconCacheState, exe: &Path, extract: impl FnOnce() -> Option<String>) -> Option<String> {
+    let key = cache_key(exe)?;
+    let dir = {
+        let cache = state.lock().unwrap();
+        if let Some(icon) = cache.memory.get(&key) {
+            return icon.clone();
+        }
+        cache.dir.clone()
+    };
+
+    let file = dir.as_ref().map(|d| d.join(format!("{}.png", key)));
+    let icon = match file.as_ref().and_then(|f| fs::read(f).ok()) {
+        Some(png) => {
+            if let Some(file) = &file {
+                touch(file);
+            }
+            (!png.is_empty()).then(|| general_purpose::STANDARD.encode(png))
+        }
+        None => {
+            let icon = extract();
+            if let (Some(dir), Some(file)) = (&dir, &file) {
+                let png = icon
+                    .as_ref()
+                    .and_then(|b64| general_purpose::STANDARD.decode(b64).ok())
+                    .unwrap_or_default();
+                // Best effort: a read-only cache dir only costs a re-extraction next run
+                if fs::create_dir_all(dir).and_then(|()| fs::write(file, png)).is_ok() {
+                    prune(dir, MAX_DISK_ENTRIES);
+                }
+            }
+            icon
+        }
+    };
+    state.lock().unwrap().memory.insert(key, icon.clone());
+    icon
+}
+
+// Marks a disk entry as recently used; reads alone do not update any timestamp
+// reliably (noatime mounts)
+fn touch(file: &Path) {
+    let _ = fs::File::options()
+        .write(true)
+        .open(file)
+        .and_then(|f| f.set_modified(SystemTime::now()));
+}
+
+// Deletes the least recently used entries until at most `keep` remain
+fn prune(dir: &Path, keep: usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "png"))
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    if files.len() <= keep {
+        return;
+    }
+    files.sort();
+    for (_, path) in &files[..files.len() - keep] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sys-dock-icons-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn key_follows_path_and_mtime() {
+        let dir = scratch("key");
+        let exe = dir.join("app");
+        fs::write(&exe, b"v1").unwrap();
+        let first = cache_key(&exe).unwrap();
+        assert_eq!(cache_key(&exe).unwrap(), first);
+
+        let file = fs::File::options().write(true).open(&exe).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+        assert_ne!(cache_key(&exe).unwrap(), first);
+        assert_eq!(cache_key(&dir.join("missing")), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn extracts_once_then_serves_from_memory_and_disk() {
+        let dir = scratch("lookup");
+        let (with_icon, without_icon) = (dir.join("app"), dir.join("daemon"));
+        fs::write(&with_icon, b"").unwrap();
+        fs::write(&without_icon, b"").unwrap();
+        let png = general_purpose::STANDARD.encode(b"\x89PNG fake");
+
+        let extractions = Cell::new(0);
+        let extract = |icon: Option<&str>| {
+            let icon = icon.map(str::to_string);
+            let extractions = &extractions;
+            move || {
+                extractions.set(extractions.get() + 1);
+                icon
+            }
+        };
+        let state: IconCacheState = Arc::new(Mutex::new(IconCache::new()));
+        state.lock().unwrap().set_dir(dir.join("cache"));
+
+        assert_eq!(lookup(&state, &with_icon, extract(Some(&png))), Some(png.clone()));
+        assert_eq!(lookup(&state, &with_icon, extract(Some(&png))), Some(png.clone()));
+        assert_eq!(lookup(&state, &without_icon, extract(None)), None);
+        assert_eq!(extractions.get(), 2);
+
+        // A fresh process finds both results on disk, including the missing icon
+        let restarted: IconCacheState = Arc::new(Mutex::new(IconCache::new()));
+        restarted.lock().unwrap().set_dir(dir.join("cache"));
+        assert_eq!(lookup(&restarted, &with_icon, extract(None)), Some(png));
+        assert_eq!(lookup(&restarted, &without_icon, extract(Some("unused"))), None);
+        assert_eq!(extractions.get(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn prunes_the_least_recently_used_entries() {
+        let dir = scratch("prune");
+        let now = SystemTime::now();
+        for (i, name) in ["old", "older", "recent", "newest"].iter().enumerate() {
+            let path = dir.join(format!("{}.png", name));
+            fs::write(&path, b"").unwrap();
+            let age = Duration::from_secs([100, 200, 10, 0][i]);
+            fs::File::options().write(true).open(&path).unwrap().set_modified(now - age).unwrap();
+        }
+        fs::write(dir.join("notes.txt"), b"").unwrap();
+
+        // Using an entry keeps it
+        touch(&dir.join("older.png"));
+        prune(&dir, 2);
+        let mut left: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec!["newest.png", "notes.txt", "older.png"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,149 @@
+use get_if_addrs::{get_if_addrs, IfAddr};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr};
+
+#[derive(Serialize, Clone, Debug)]
+pub struct IpAddressInfo {
+    pub address: String,
+    pub family: String, // "ipv4" or "ipv6"
+    pub prefix_len: u8,
+    pub netmask: String,
+    pub broadcast: Option<String>,
+    pub scope: String, // "host", "link", "site" or "global"
+}
+
+fn prefix_len(netmask: IpAddr) -> u8 {
+    match netmask {
+        IpAddr::V4(mask) => u32::from(mask).count_ones() as u8,
+        IpAddr::V6(mask) => u128::from(mask).count_ones() as u8,
+    }
+}
+
+fn scope(ip: IpAddr) -> &'static str {
+    match ip {
+        IpAddr::V4(v4) if v4.is_loopback() => "host",
+        IpAddr::V4(v4) if v4.is_link_local() => "link",
+        IpAddr::V4(_) => "global",
+        IpAddr::V6(v6) => ipv6_scope(v6),
+    }
+}
+
+// Scope as the kernel would report it; unique-local (fc00::/7) counts as global
+fn ipv6_scope(ip: Ipv6Addr) -> &'static str {
+    let first = ip.segments()[0];
+    if ip.is_loopback() {
+        "host"
+    } else if first & 0xffc0 == 0xfe80 {
+        "link"
+    } else if first & 0xffc0 == 0xfec0 {
+        "site"
+    } else {
+        "global"
+    }
+}
+
+// Addresses keyed by the interface name sysinfo uses for the same adapter
+pub fn addresses_by_interface() -> HashMap<String, Vec<IpAddressInfo>> {
+    let mut by_name: HashMap<String, Vec<IpAddressInfo>> = HashMap::new();
+    let Ok(if_addrs) = get_if_addrs() else {
+        return by_name;
+    };
+
+    for iface in if_addrs {
+        let (ip, netmask, broadcast, family) = match iface.addr {
+            IfAddr::V4(v4) => (
+                IpAddr::V4(v4.ip),
+                IpAddr::V4(v4.netmask),
+                v4.broadcast.map(IpAddr::V4),
+                "ipv4",
+            ),
+            IfAddr::V6(v6) => (
+                IpAddr::V6(v6.ip),
+                IpAddr::V6(v6.netmask),
+                v6.broadcast.map(IpAddr::V6),
+                "ipv6",
+            ),
+        };
+
+        by_name
+            .entry(sysinfo_interface_name(&iface.name))
+            .or_default()
+            .push(IpAddressInfo {
+                address: ip.to_string(),
+                family: family.to_string(),
+                prefix_len: prefix_len(netmask),
+                netmask: netmask.to_string(),
+                broadcast: broadcast.map(|b| b.to_string()),
+                scope: scope(ip).to_string(),
+            });
+    }
+
+    for addresses in by_name.values_mut() {
+        addresses.sort_by(|a, b| a.family.cmp(&b.family).then_with(|| a.address.cmp(&b.address)));
+        addresses.dedup_by(|a, b| a.address == b.address);
+    }
+    by_name
+}
+
+// Unix tools and sysinfo agree on interface names
+#[cfg(not(target_os = "windows"))]
+fn sysinfo_interface_name(name: &str) -> String {
+    name.to_string()
+}
+
+// get_if_addrs reports the adapter GUID ("{4D36E972-...}") while sysinfo uses the
+// interface alias ("Ethernet"), so translate through the interface LUID
+#[cfg(target_os = "windows")]
+fn sysinfo_interface_name(name: &str) -> String {
+    use windows::core::GUID;
+    use windows::Win32::NetworkManagement::IpHelper::{ConvertInterfaceGuidToLuid, ConvertInterfaceLuidToAlias};
+    use windows::Win32::NetworkManagement::Ndis::NET_LUID_LH;
+
+    let guid_str = name.trim_start_matches('{').trim_end_matches('}');
+    let is_guid = guid_str.len() == 36
+        && guid_str.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    if !is_guid {
+        return name.to_string();
+    }
+
+    unsafe {
+        let guid = GUID::from(guid_str);
+        let mut luid: NET_LUID_LH = std::mem::zeroed();
+        if ConvertInterfaceGuidToLuid(&guid, &mut luid).is_err() {
+            return name.to_string();
+        }
+        // NDIS_IF_MAX_STRING_SIZE + 1
+        let mut alias = [0u16; 257];
+        if ConvertInterfaceLuidToAlias(&luid, &mut alias).is_err() {
+            return name.to_string();
+        }
+        let len = alias.iter().position(|&c| c == 0).unwrap_or(alias.len());
+        String::from_utf16_lossy(&alias[..len])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_length_from_netmask() {
+        assert_eq!(prefix_len("255.255.255.0".parse().unwrap()), 24);
+        assert_eq!(prefix_len("255.255.240.0".parse().unwrap()), 20);
+        assert_eq!(prefix_len("ffff:ffff:ffff:ffff::".parse().unwrap()), 64);
+    }
+
+    #[test]
+    fn classifies_address_scope() {
+        assert_eq!(scope("127.0.0.1".parse().unwrap()), "host");
+        assert_eq!(scope("169.254.10.2".parse().unwrap()), "link");
+        assert_eq!(scope("192.168.1.20".parse().unwrap()), "global");
+        assert_eq!(scope("::1".parse().unwrap()), "host");
+        assert_eq!(scope("fe80::1c2b:3ff:fe4d:5e6f".parse().unwrap()), "link");
+        assert_eq!(scope("fd12:3456::1".parse().unwrap()), "global");
+    }
+}
@@ -2,12 +2,63 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use serde::Serialize;
-use sysinfo::{NetworkExt, System, SystemExt, CpuExt, DiskExt, ProcessExt};
-use get_if_addrs::{get_if_addrs, IfAddr, Ifv4Addr, Ifv6Addr};
+use sysinfo::{NetworkExt, System, SystemExt, CpuExt, DiskExt, ProcessExt, UserExt};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod cgroups;
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod connections;
+mod diskstats;
+mod iconcache;
+#[cfg(target_os = "linux")]
+mod drm;
+mod ifaddrs;
+#[cfg(target_os = "linux")]
+mod netdev;
+mod netrates;
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod pci;
+mod procctl;
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod procdetails;
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod procmem;
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+mod procnet;
+mod procio;
+mod proctrack;
+mod proctree;
+mod protection;
+mod sampler;
+mod sensors;
+mod stream;
+mod watchdog;
+#[cfg(target_os = "linux")]
+mod xdgicons;
+
+use cgroups::{GroupBy, ProcessGroup};
+use connections::Connection;
+use diskstats::DiskIoRate;
+use iconcache::{IconCache, IconCacheState};
+use ifaddrs::IpAddressInfo;
+use netrates::{NetHistory, NetRate};
+use pci::PciDevice;
+use procctl::{ControlError, IoPriorityClass, ProcessTarget, SchedulingInfo, TerminationResult};
+use procdetails::ProcessDetails;
+use procio::ProcessIoUsage;
+use procmem::MemoryBreakdown;
+use proctrack::CpuNormalization;
+use proctree::ProcessTreeNode;
+use protection::{ProtectionPolicy, ProtectionRule, ProtectionState, ProtectionStatus};
+use sampler::{Sampler, SamplerConfig, SamplerState};
+use sensors::Sensor;
+use stream::{StreamHub, StreamState, Subscription};
+use watchdog::{ActionRecord, WatchdogRule, WatchdogStatus};
+use tauri::Manager;
+
 #[tauri::command]
 fn get_username() -> String {
     std::env::var("USERNAME")
@@ -64,6 +115,7 @@ struct DiskInfo {
     available: u64,
     percentage: f64,
     type_: String, // <-- Add this field
+    io: Option<DiskIoRate>,
 }
 
 #[derive(Serialize)]
@@ -85,18 +137,38 @@ struct CpuInfo {
     processes: Option<usize>,
     handles: Option<usize>,
     uptime: Option<u64>,
+    per_core: Vec<CoreInfo>,
 }
 
 #[derive(Serialize)]
+struct CoreInfo {
+    index: usize,
+    usage: Option<f32>,
+    frequency: Option<u64>,     // MHz
+    min_frequency: Option<u64>, // MHz
+    max_frequency: Option<u64>, // MHz
+    online: bool,
+}
+
+#[derive(Serialize, Default)]
 struct GpuInfo {
     #[serde(rename = "name")]
     name: String,
     #[serde(rename = "adapter_ram")]
-    ram: Option<u32>,
+    ram: Option<u32>, // MB on every platform
     #[serde(rename = "driver_version")]
     driver_version: Option<String>,
     #[serde(rename = "vram_usage")]
     vram_usage: Option<u64>, // in bytes
+    utilization: Option<f32>, // percent
+    pci_slot: Option<String>,
+    vendor_id: Option<String>,
+    device_id: Option<String>,
+    driver: Option<String>,
+    core_clock_mhz: Option<u64>,
+    max_core_clock_mhz: Option<u64>,
+    memory_clock_mhz: Option<u64>,
+    temperature: Option<f32>,
 }
 
 #[derive(Serialize)]
@@ -107,15 +179,33 @@ struct SystemOverview {
     gpus: Vec<GpuInfo>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Default)]
 struct ProcessInfo {
     name: String,
     cpu: f32,
-    memory: u64,
+    rss_bytes: u64,
+    pss_bytes: Option<u64>,    // Linux only, and only where smaps_rollup is readable
+    uss_bytes: Option<u64>,
+    shared_bytes: Option<u64>,
+    swap_bytes: Option<u64>,
     pid: i32,
+    ppid: Option<i32>,
+    uid: Option<String>,  // numeric uid on Unix, SID on Windows
+    user: Option<String>,
+    state: String,        // "running", "sleeping", "zombie", ...
+    threads: Option<u32>,
+    start_time: u64,      // unix seconds
     exe: Option<String>,
-    icon: Option<String>, // base64 PNG string or None
+    icon: Option<String>, // changes with the executable; the icon itself comes from fetch_process_icon(exe)
     runtime: Option<u64>, // runtime in seconds
+    net_rx_bytes_per_sec: Option<f64>, // TCP only; None where socket counters are unavailable
+    net_tx_bytes_per_sec: Option<f64>,
+    disk_read_bytes_per_sec: Option<f64>, // None until the process has been sampled twice
+    disk_write_bytes_per_sec: Option<f64>,
+    cgroup: Option<String>,       // cgroup v2 path; Linux only
+    unit: Option<String>,         // owning systemd unit, e.g. "sshd.service"
+    slice: Option<String>,
+    container_id: Option<String>, // docker, podman or containerd
 }
 
 #[derive(Serialize)]
@@ -129,10 +219,12 @@ struct NetworkInterface {
     errors: u64,
     drops: u64,
     ip_addresses: Vec<String>,      // NEW
+    addresses: Vec<IpAddressInfo>,
     mac_address: Option<String>,    // NEW
     interface_type: Option<String>, // NEW
     link_speed_mbps: Option<u32>,   // NEW
     last_updated_unix: u64,         // NEW
+    rate: Option<NetRate>,
 }
 
 #[derive(Serialize)]
@@ -140,18 +232,16 @@ struct NetworkInfo {
     interfaces: Vec<NetworkInterface>,
 }
 
-// If you plan to use AppState for process sampling,
-// you can define and use it in your Tauri app as needed.
-
 #[tauri::command]
-fn fetch_system_overview() -> SystemOverview {
-    let mut sys = System::new();
-    sys.refresh_disks_list();
-    sys.refresh_disks();
-    sys.refresh_all();
+fn fetch_system_overview(state: tauri::State<SamplerState>) -> SystemOverview {
+    // GPU probing may shell out, so keep it outside the sampler lock
+    let gpus = fetch_gpu_info();
+    let sampler = state.lock().unwrap();
+    build_system_overview(&sampler, gpus)
+}
 
-    std::thread::sleep(std::time::Duration::from_millis(100));
-    sys.refresh_cpu();
+fn build_system_overview(sampler: &Sampler, gpus: Vec<GpuInfo>) -> SystemOverview {
+    let sys = &sampler.sys;
 
     // Memory
     let total_memory = sys.total_memory();
@@ -190,7 +280,7 @@ fn fetch_system_overview() -> SystemOverview {
     {
         cpu_frequency = Some(sys.global_cpu_info().frequency() as u64);
         cpu_threads = Some(num_cpus::get() as u32);
-        cpu_temperature = fetch_cpu_temp_linux();
+        cpu_temperature = sensors::cpu_package_temperature(&sampler.sensors);
     }
     
     #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
@@ -202,6 +292,7 @@ fn fetch_system_overview() -> SystemOverview {
     let cpu_processes = Some(sys.processes().len());
     let cpu_handles = None; // sysinfo does not provide handles
     let cpu_uptime = Some(sys.uptime());
+    let per_core = build_per_core(sys);
 
     // Disks
     let disks: Vec<DiskInfo> = sys.disks().iter().map(|disk| {
@@ -213,20 +304,20 @@ fn fetch_system_overview() -> SystemOverview {
         } else {
             0.0
         };
+        let name = disk.name().to_string_lossy().into_owned();
+        let io = sampler.disk_io.rate_for(&name).cloned();
         DiskInfo {
-            name: disk.name().to_string_lossy().into_owned(),
+            name,
             mount_point: disk.mount_point().to_string_lossy().into_owned(),
             total,
             used,
             available,
             percentage,
             type_: format!("{:?}", disk.kind()), // <-- Use disk.kind() instead of disk.type_
+            io,
         }
     }).collect();
 
-    // GPU info - cross-platform
-    let gpus = fetch_gpu_info();
-
     SystemOverview {
         memory: MemoryInfo {
             total: total_memory,
@@ -244,6 +335,7 @@ fn fetch_system_overview() -> SystemOverview {
             processes: cpu_processes,
             handles: cpu_handles,
             uptime: cpu_uptime,
+            per_core,
         },
         disks,
         gpus, // Only main GPU included
@@ -251,26 +343,152 @@ fn fetch_system_overview() -> SystemOverview {
 }
 
 #[tauri::command]
-fn fetch_processes() -> Vec<ProcessInfo> {
-    let mut sys = System::new_all();
-    sys.refresh_all();
+fn fetch_processes(
+    state: tauri::State<SamplerState>,
+    cpu_mode: Option<CpuNormalization>,
+) -> Vec<ProcessInfo> {
+    let sampler = state.lock().unwrap();
+    build_processes(&sampler, cpu_mode.unwrap_or_default())
+}
+
+// Icons are fetched separately so the process list stays small; the frontend
+// asks once per cache key from fetch_processes
+#[tauri::command(async)]
+fn fetch_process_icon(state: tauri::State<IconCacheState>, exe: String) -> Option<String> {
+    iconcache::lookup(&state, std::path::Path::new(&exe), || extract_icon_base64(&exe).ok())
+}
+
+// Processes nested under their parents, with CPU and memory summed per subtree
+#[tauri::command]
+fn fetch_process_tree(
+    state: tauri::State<SamplerState>,
+    cpu_mode: Option<CpuNormalization>,
+) -> Vec<ProcessTreeNode> {
+    let processes = {
+        let sampler = state.lock().unwrap();
+        build_processes(&sampler, cpu_mode.unwrap_or_default())
+    };
+    proctree::build_tree(processes)
+}
+
+// CPU, memory and disk I/O summed per systemd unit, slice, container or cgroup,
+// with the group's memory.max/cpu.max limits
+#[tauri::command]
+fn fetch_process_groups(
+    state: tauri::State<SamplerState>,
+    by: GroupBy,
+    cpu_mode: Option<CpuNormalization>,
+) -> Result<Vec<ProcessGroup>, String> {
+    #[cfg(target_os = "linux")]
+    {
+        let sampler = state.lock().unwrap();
+        let members = sampler
+            .sys
+            .processes()
+            .iter()
+            .filter_map(|(pid, proc)| {
+                let id: i32 = pid.to_string().parse().ok()?;
+                let memory = sampler.process_memory.breakdown_for(*pid);
+                let io = sampler.process_io.rate_for(*pid);
+                Some(cgroups::GroupMember {
+                    pid: id,
+                    membership: sampler.cgroups.membership_for(*pid)?.clone(),
+                    cpu: sampler.processes.cpu_usage(*pid, cpu_mode.unwrap_or_default()),
+                    memory: memory.and_then(|m| m.pss).unwrap_or_else(|| proc.memory()),
+                    disk_read_bytes_per_sec: io.map(|r| r.read_bytes_per_sec),
+                    disk_write_bytes_per_sec: io.map(|r| r.write_bytes_per_sec),
+                })
+            })
+            .collect();
+        Ok(cgroups::group_processes(members, by, std::path::Path::new(cgroups::CGROUP_ROOT)))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (state, by, cpu_mode);
+        Err("cgroup grouping is only available on Linux".to_string())
+    }
+}
+
+// Runs off the main thread: per-thread CPU is measured over a short sampling window
+#[tauri::command(async)]
+fn fetch_process_details(pid: u32) -> Result<ProcessDetails, String> {
+    #[cfg(target_os = "linux")]
+    {
+        procdetails::read_details(std::path::Path::new(connections::PROC_ROOT), pid)
+    }
 
-    sys.processes()
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        Err("Process details are only available on Linux".to_string())
+    }
+}
+
+fn process_state(status: sysinfo::ProcessStatus) -> String {
+    use sysinfo::ProcessStatus;
+    match status {
+        ProcessStatus::Run => "running",
+        ProcessStatus::Sleep => "sleeping",
+        ProcessStatus::Idle => "idle",
+        ProcessStatus::Stop => "stopped",
+        ProcessStatus::Zombie => "zombie",
+        ProcessStatus::Tracing => "tracing",
+        ProcessStatus::Dead => "dead",
+        ProcessStatus::UninterruptibleDiskSleep => "disk_sleep",
+        ProcessStatus::Wakekill | ProcessStatus::Waking | ProcessStatus::Parked | ProcessStatus::LockBlocked => "waiting",
+        ProcessStatus::Unknown(_) => "unknown",
+    }
+    .to_string()
+}
+
+fn build_processes(sampler: &Sampler, cpu_mode: CpuNormalization) -> Vec<ProcessInfo> {
+    sampler.sys.processes()
         .values()
         .map(|proc| {
             let exe_path = proc.exe().to_str().map(|s| s.to_string());
             let icon = exe_path
                 .as_ref()
-                .and_then(|path| extract_icon_base64(path).ok());
+                .and_then(|path| iconcache::cache_key(std::path::Path::new(path)));
             let runtime = proc.run_time(); // Get runtime in seconds
+            let net = sampler.process_net.rate_for(proc.pid());
+            let io = sampler.process_io.rate_for(proc.pid());
+            let pid = proc.pid().to_string().parse::<i32>().unwrap_or(0);
+            let membership = sampler.cgroups.membership_for(proc.pid());
+            // sysinfo reports resident memory in bytes
+            let memory = sampler.process_memory.breakdown_for(proc.pid()).unwrap_or(MemoryBreakdown {
+                rss: proc.memory(),
+                ..Default::default()
+            });
             ProcessInfo {
                 name: proc.name().to_string(),
-                cpu: proc.cpu_usage(),
-                memory: proc.memory() / 1024, // <-- Divide by 1024 to convert KB to MB
-                pid: proc.pid().to_string().parse::<i32>().unwrap_or(0),
+                cpu: sampler.processes.cpu_usage(proc.pid(), cpu_mode),
+                rss_bytes: memory.rss,
+                pss_bytes: memory.pss,
+                uss_bytes: memory.uss,
+                shared_bytes: memory.shared,
+                swap_bytes: memory.swap,
+                pid,
+                ppid: proc.parent().and_then(|p| p.to_string().parse::<i32>().ok()),
+                uid: proc.user_id().map(|uid| (**uid).to_string()),
+                user: proc
+                    .user_id()
+                    .and_then(|uid| sampler.sys.get_user_by_id(uid))
+                    .map(|u| u.name().to_string()),
+                state: process_state(proc.status()),
+                threads: sampler.processes.thread_count(proc.pid()),
+                start_time: proc.start_time(),
                 exe: exe_path,
                 icon,
                 runtime: Some(runtime),
+                net_rx_bytes_per_sec: net.map(|n| n.rx_bytes_per_sec),
+                net_tx_bytes_per_sec: net.map(|n| n.tx_bytes_per_sec),
+                disk_read_bytes_per_sec: io.map(|r| r.read_bytes_per_sec),
+                disk_write_bytes_per_sec: io.map(|r| r.write_bytes_per_sec),
+                unit: membership.and_then(|m| m.unit.as_ref()).map(|u| u.name.clone()),
+                slice: membership.and_then(|m| m.slice.as_ref()).map(|s| s.name.clone()),
+                container_id: membership.and_then(|m| m.container.as_ref()).map(|c| c.id.clone()),
+                cgroup: membership.map(|m| m.path.clone()),
             }
         })
         .collect()
@@ -385,9 +603,24 @@ fn extract_icon_base64(exe_path: &str) -> Result<String, ()> {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
+// The icon of the application's .desktop entry, looked up in the user's icon theme.
+// Processes without a desktop entry get no icon.
+#[cfg(target_os = "linux")]
+fn extract_icon_base64(exe_path: &str) -> Result<String, ()> {
+    use base64::engine::general_purpose;
+    use base64::Engine;
+    use std::sync::OnceLock;
+
+    // Desktop files and theme directories are indexed once per run
+    static ICONS: OnceLock<xdgicons::IconLookup> = OnceLock::new();
+    let icons = ICONS.get_or_init(xdgicons::IconLookup::from_environment);
+    let png = icons.icon_png(std::path::Path::new(exe_path), 32).ok_or(())?;
+    Ok(general_purpose::STANDARD.encode(png))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 fn extract_icon_base64(exe_path: &str) -> Result<String, ()> {
-    // macOS and Linux: Create a simple colored square as placeholder
+    // macOS: Create a simple colored square as placeholder
     // In a production app, you'd integrate with platform-specific APIs
     use image::{ImageBuffer, Rgba};
     use base64::engine::general_purpose;
@@ -432,34 +665,24 @@ fn extract_icon_base64(exe_path: &str) -> Result<String, ()> {
 }
 
 #[tauri::command]
-fn fetch_network_info() -> NetworkInfo {
-    let mut sys = System::new();
-    sys.refresh_networks_list();
-    
-    // Refresh twice to get accurate deltas
-    sys.refresh_networks();
-    std::thread::sleep(std::time::Duration::from_millis(100));
-    sys.refresh_networks();
-
-    // collect addresses per interface name (flattened)
-    let mut addrs: Vec<(String, String)> = Vec::new();
-    if let Ok(if_addrs) = get_if_addrs() {
-        for iface in if_addrs {
-            match iface.addr {
-                IfAddr::V4(Ifv4Addr { ip, .. }) => {
-                    addrs.push((iface.name.clone(), ip.to_string()))
-                }
-                IfAddr::V6(Ifv6Addr { ip, .. }) => {
-                    addrs.push((iface.name.clone(), ip.to_string()))
-                }
-            }
-        }
-    }
+fn fetch_network_info(state: tauri::State<SamplerState>) -> NetworkInfo {
+    let sampler = state.lock().unwrap();
+    build_network_info(&sampler)
+}
 
-    // normalizer: remove non-alphanumeric and lowercase for fuzzy matching
-    fn norm(s: &str) -> String {
-        s.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase()
-    }
+// Rates per interface over the last `seconds` (default 60), oldest sample first
+#[tauri::command]
+fn fetch_network_history(
+    state: tauri::State<SamplerState>,
+    interface: Option<String>,
+    seconds: Option<u64>,
+) -> Vec<NetHistory> {
+    let span = std::time::Duration::from_secs(seconds.unwrap_or(60));
+    state.lock().unwrap().network.history(interface.as_deref(), span)
+}
+
+fn build_network_info(sampler: &Sampler) -> NetworkInfo {
+    let mut addresses_by_interface = ifaddrs::addresses_by_interface();
 
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -468,7 +691,7 @@ fn fetch_network_info() -> NetworkInfo {
 
     let mut interfaces = Vec::new();
 
-    for (name, data) in sys.networks() {
+    for (name, data) in sampler.sys.networks() {
         // Get total cumulative bytes (these are totals since interface was up)
         let bytes_received = data.total_received();
         let bytes_transmitted = data.total_transmitted();
@@ -476,52 +699,39 @@ fn fetch_network_info() -> NetworkInfo {
         let packets_received = data.total_packets_received();
         let packets_transmitted = data.total_packets_transmitted();
         let errors = data.total_errors_on_received() + data.total_errors_on_transmitted();
-        let drops = 0u64;
-
-        // fuzzy-match addresses collected from get_if_addrs
-        let n_norm = norm(name);
-        let mut ip_addresses: Vec<String> = addrs
-            .iter()
-            .filter_map(|(iface_name, ip)| {
-                let i_norm = norm(iface_name);
-                if i_norm == n_norm || i_norm.contains(&n_norm) || n_norm.contains(&i_norm) {
-                    Some(ip.clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
 
-        // looser match if nothing
-        if ip_addresses.is_empty() {
-            ip_addresses = addrs
-                .iter()
-                .filter_map(|(iface_name, ip)| {
-                    let i_norm = norm(iface_name);
-                    if i_norm.starts_with(&n_norm) || i_norm.ends_with(&n_norm) || n_norm.starts_with(&i_norm) || n_norm.ends_with(&i_norm) {
-                        Some(ip.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+        // Exact match by interface name; no address means an empty list
+        let addresses = addresses_by_interface.remove(name.as_str()).unwrap_or_default();
+        let ip_addresses: Vec<String> = addresses.iter().map(|a| a.address.clone()).collect();
+
+        // sysinfo knows the MAC everywhere; Linux sysfs adds link type, speed and state
+        let mac = data.mac_address();
+        let sysinfo_mac = if mac.is_unspecified() { None } else { Some(mac.to_string()) };
+        let mac_address;
+        let interface_type;
+        let link_speed_mbps;
+        let drops;
+        let status;
+
+        #[cfg(target_os = "linux")]
+        {
+            let link = netdev::read_link_info(std::path::Path::new(netdev::FS_ROOT), name);
+            status = netdev::status_from_operstate(&link);
+            mac_address = link.mac_address.or(sysinfo_mac);
+            interface_type = link.interface_type;
+            link_speed_mbps = link.link_speed_mbps;
+            drops = link.rx_dropped + link.tx_dropped;
         }
 
-        // final fallback: include all non-loopback addresses (so UI sees something)
-        if ip_addresses.is_empty() {
-            ip_addresses = addrs
-                .iter()
-                .filter_map(|(_, ip)| {
-                    if !ip.starts_with("127.") && !ip.starts_with("::1") { Some(ip.clone()) } else { None }
-                })
-                .collect();
+        #[cfg(not(target_os = "linux"))]
+        {
+            status = if !ip_addresses.is_empty() { "Connected".to_string() } else { "Disconnected".to_string() };
+            mac_address = sysinfo_mac;
+            interface_type = None;
+            link_speed_mbps = None;
+            drops = 0u64;
         }
 
-        ip_addresses.sort();
-        ip_addresses.dedup();
-
-        let status = if !ip_addresses.is_empty() { "Connected".to_string() } else { "Disconnected".to_string() };
-
         interfaces.push(NetworkInterface {
             name: name.to_string(),
             status,
@@ -532,30 +742,236 @@ fn fetch_network_info() -> NetworkInfo {
             errors,
             drops,
             ip_addresses,
-            mac_address: None,
-            interface_type: None,
-            link_speed_mbps: None,
+            addresses,
+            mac_address,
+            interface_type,
+            link_speed_mbps,
             last_updated_unix: now,
+            rate: sampler.network.rate_for(name).cloned(),
         });
     }
 
     NetworkInfo { interfaces }
 }
 
+// Protected processes are refused before any signal or priority change is sent
+fn check_protection(
+    protection: &ProtectionState,
+    sampler: &SamplerState,
+    target: ProcessTarget,
+) -> Result<(), ControlError> {
+    // Same lock order as the sampler's watchdog pass
+    let mut sampler = sampler.lock().unwrap();
+    let policy = protection.lock().unwrap();
+    policy.check(&mut sampler.sys, target)
+}
+
+// SIGTERM, then SIGKILL after `grace_ms` (default 3000). Runs off the main thread
+// and without the sampler lock, since it may wait for the whole grace period.
+#[tauri::command(async)]
+fn end_process(
+    state: tauri::State<SamplerState>,
+    protection: tauri::State<ProtectionState>,
+    pid: i32,
+    grace_ms: Option<u64>,
+) -> Result<TerminationResult, ControlError> {
+    check_protection(&protection, &state, ProcessTarget::Pid(pid))?;
+    let grace = Duration::from_millis(grace_ms.unwrap_or(3000));
+    let result = procctl::terminate(pid, grace);
+    // Drop the entry from the cached table right away instead of on the next refresh
+    state.lock().unwrap().sys.refresh_process(sysinfo::Pid::from(pid as usize));
+    Ok(result)
+}
+
+#[tauri::command]
+fn suspend_process(
+    state: tauri::State<SamplerState>,
+    protection: tauri::State<ProtectionState>,
+    target: ProcessTarget,
+) -> Result<(), ControlError> {
+    check_protection(&protection, &state, target)?;
+    procctl::suspend(target)
+}
+
+#[tauri::command]
+fn resume_process(target: ProcessTarget) -> Result<(), ControlError> {
+    procctl::resume(target)
+}
+
 #[tauri::command]
-fn end_process(pid: i32) -> Result<(), String> {
-    let mut sys = System::new_all();
-    sys.refresh_processes();
-    let sys_pid = sysinfo::Pid::from(pid as usize);
-    if let Some(process) = sys.process(sys_pid) {
-        process.kill();
+fn set_process_priority(
+    state: tauri::State<SamplerState>,
+    protection: tauri::State<ProtectionState>,
+    target: ProcessTarget,
+    nice: i32,
+) -> Result<(), ControlError> {
+    check_protection(&protection, &state, target)?;
+    procctl::set_nice(target, nice)
+}
+
+#[tauri::command]
+fn set_io_priority(
+    state: tauri::State<SamplerState>,
+    protection: tauri::State<ProtectionState>,
+    target: ProcessTarget,
+    class: IoPriorityClass,
+    level: Option<u8>,
+) -> Result<(), ControlError> {
+    check_protection(&protection, &state, target)?;
+    procctl::set_io_priority(target, class, level)
+}
+
+#[tauri::command]
+fn set_cpu_affinity(
+    state: tauri::State<SamplerState>,
+    protection: tauri::State<ProtectionState>,
+    target: ProcessTarget,
+    cpus: Vec<usize>,
+) -> Result<(), ControlError> {
+    check_protection(&protection, &state, target)?;
+    procctl::set_affinity(target, &cpus)
+}
+
+#[tauri::command]
+fn get_process_scheduling(pid: i32) -> Result<SchedulingInfo, ControlError> {
+    procctl::scheduling_info(pid)
+}
+
+// The processes doing the most disk I/O right now, busiest first
+#[tauri::command]
+fn fetch_top_io_processes(state: tauri::State<SamplerState>, limit: Option<usize>) -> Vec<ProcessIoUsage> {
+    let sampler = state.lock().unwrap();
+    sampler.process_io.top(&sampler.sys, limit.unwrap_or(10))
+}
+
+// Throughput for every block device, including ones without a mounted filesystem
+#[tauri::command]
+fn fetch_disk_io(state: tauri::State<SamplerState>) -> Vec<DiskIoRate> {
+    state.lock().unwrap().disk_io.rates().to_vec()
+}
+
+#[tauri::command]
+fn fetch_sensors(state: tauri::State<SamplerState>) -> Vec<Sensor> {
+    state.lock().unwrap().sensors.clone()
+}
+
+// Async so the sysfs walk does not block the main thread
+#[tauri::command(async)]
+fn fetch_pci_devices() -> Result<Vec<PciDevice>, String> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(pci::list_devices(std::path::Path::new(pci::PCI_DEVICES_ROOT)))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err("PCI device inventory is only available on Linux".to_string())
+    }
+}
+
+// Open TCP and UDP sockets with their owning processes, like `ss -tuap`. Runs
+// off the main thread: it parses /proc/net/* and walks every process's fd table.
+#[tauri::command(async)]
+fn fetch_connections() -> Result<Vec<Connection>, String> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(connections::list_connections(std::path::Path::new(connections::PROC_ROOT)))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err("Connection listing is only available on Linux".to_string())
+    }
+}
+
+#[tauri::command]
+fn get_protection_rules(state: tauri::State<ProtectionState>) -> Vec<ProtectionRule> {
+    state.lock().unwrap().rules().to_vec()
+}
+
+// Saves the rule list; this app's own pid stays protected whatever it contains
+#[tauri::command]
+fn set_protection_rules(state: tauri::State<ProtectionState>, rules: Vec<ProtectionRule>) -> Result<(), String> {
+    state.lock().unwrap().set_rules(rules)
+}
+
+#[tauri::command]
+fn reset_protection_rules(state: tauri::State<ProtectionState>) -> Result<Vec<ProtectionRule>, String> {
+    let mut policy = state.lock().unwrap();
+    policy.reset()?;
+    Ok(policy.rules().to_vec())
+}
+
+// Where rules are stored and why they failed to load at startup, if they did
+#[tauri::command]
+fn fetch_protection_status(state: tauri::State<ProtectionState>) -> ProtectionStatus {
+    state.lock().unwrap().status()
+}
+
+#[tauri::command]
+fn get_watchdog_rules(state: tauri::State<SamplerState>) -> Vec<WatchdogRule> {
+    state.lock().unwrap().watchdog.rules().to_vec()
+}
+
+// Validates and saves the whole rule list; rules take effect on the next process refresh
+#[tauri::command]
+fn set_watchdog_rules(state: tauri::State<SamplerState>, rules: Vec<WatchdogRule>) -> Result<(), String> {
+    state.lock().unwrap().watchdog.set_rules(rules)
+}
+
+#[tauri::command]
+fn fetch_watchdog_history(state: tauri::State<SamplerState>) -> Vec<ActionRecord> {
+    state.lock().unwrap().watchdog.history().cloned().collect()
+}
+
+// Where rules are stored and why they failed to load at startup, if they did
+#[tauri::command]
+fn fetch_watchdog_status(state: tauri::State<SamplerState>) -> WatchdogStatus {
+    state.lock().unwrap().watchdog.status()
+}
+
+#[tauri::command]
+fn clear_watchdog_history(state: tauri::State<SamplerState>) {
+    state.lock().unwrap().watchdog.clear_history();
+}
+
+#[tauri::command]
+fn get_sampler_config(state: tauri::State<SamplerState>) -> SamplerConfig {
+    state.lock().unwrap().config().clone()
+}
+
+#[tauri::command]
+fn set_sampler_config(state: tauri::State<SamplerState>, config: SamplerConfig) -> Result<(), String> {
+    state.lock().unwrap().set_config(config);
+    Ok(())
+}
+
+// Push snapshots to a window (the caller's by default) instead of having it poll
+#[tauri::command]
+fn subscribe_metrics(
+    window: tauri::Window,
+    state: tauri::State<StreamState>,
+    interval_ms: u64,
+    target: Option<String>,
+) -> Subscription {
+    let target = target.unwrap_or_else(|| window.label().to_string());
+    state.lock().unwrap().subscribe(target, interval_ms)
+}
+
+#[tauri::command]
+fn unsubscribe_metrics(state: tauri::State<StreamState>, id: u64) -> Result<(), String> {
+    if state.lock().unwrap().unsubscribe(id) {
         Ok(())
     } else {
-        // Treat "not found" as success (already ended)
-        Ok(())
+        Err(format!("No metrics subscription with id {}", id))
     }
 }
 
+#[tauri::command]
+fn list_metric_subscriptions(state: tauri::State<StreamState>) -> Vec<Subscription> {
+    state.lock().unwrap().subscriptions()
+}
+
 #[tauri::command]
 fn get_disk_health(disk_path: String) -> Option<String> {
     use std::process::Command;
@@ -572,11 +988,16 @@ fn get_disk_health(disk_path: String) -> Option<String> {
     }
     None
 }
-#[allow(dead_code)]
+
 #[tauri::command]
-fn check_alerts(cpu_threshold: f32, ram_threshold: f64, disk_threshold: f64) -> Vec<String> {
-    let mut sys = System::new_all();
-    sys.refresh_all();
+fn check_alerts(
+    state: tauri::State<SamplerState>,
+    cpu_threshold: f32,
+    ram_threshold: f64,
+    disk_threshold: f64,
+) -> Vec<String> {
+    let sampler = state.lock().unwrap();
+    let sys = &sampler.sys;
 
     let mut alerts = Vec::new();
 
@@ -628,6 +1049,7 @@ type LoggerState = Arc<Mutex<PerformanceLoggerState>>;
 #[tauri::command]
 fn start_performance_logging(
     state: tauri::State<LoggerState>,
+    sampler_state: tauri::State<SamplerState>,
     interval_secs: u64,
 ) -> Result<(), String> {
     let mut logger = state.lock().unwrap();
@@ -638,6 +1060,7 @@ fn start_performance_logging(
     
     logger.is_logging = true;
     let state_clone = Arc::clone(&state.inner());
+    let sampler_clone = Arc::clone(sampler_state.inner());
     
     let handle = thread::spawn(move || {
         loop {
@@ -648,19 +1071,21 @@ fn start_performance_logging(
                 }
             }
             
-            // Collect performance data
-            let mut sys = System::new_all();
-            sys.refresh_all();
-            thread::sleep(Duration::from_millis(100));
-            sys.refresh_cpu();
-            
-            let cpu_usage = sys.global_cpu_info().cpu_usage();
-            let memory_usage = sys.used_memory();
-            let memory_total = sys.total_memory();
-            
-            let (disk_usage, disk_total) = sys.disks().iter().fold((0u64, 0u64), |(used, total), disk| {
-                (used + (disk.total_space() - disk.available_space()), total + disk.total_space())
-            });
+            // Collect performance data from the shared sampler
+            let (cpu_usage, memory_usage, memory_total, disk_usage, disk_total) = {
+                let sampler = sampler_clone.lock().unwrap();
+                let sys = &sampler.sys;
+                let (disk_usage, disk_total) = sys.disks().iter().fold((0u64, 0u64), |(used, total), disk| {
+                    (used + (disk.total_space() - disk.available_space()), total + disk.total_space())
+                });
+                (
+                    sys.global_cpu_info().cpu_usage(),
+                    sys.used_memory(),
+                    sys.total_memory(),
+                    disk_usage,
+                    disk_total,
+                )
+            };
             
             let log = PerformanceLog {
                 timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
@@ -943,6 +1368,7 @@ fn fetch_gpus_macos() -> Vec<GpuInfo> {
                                 ram: vram,
                                 driver_version: None,
                                 vram_usage: None,
+                                ..Default::default()
                             });
                         }
                     }
@@ -958,72 +1384,148 @@ fn fetch_gpus_macos() -> Vec<GpuInfo> {
             ram: None,
             driver_version: None,
             vram_usage: None,
+            ..Default::default()
         });
     }
     
     gpus
 }
 
-// Linux GPU detection
+// Linux GPU detection: one entry per PCI display device under /sys/class/drm
 #[cfg(target_os = "linux")]
 fn fetch_gpus_linux() -> Vec<GpuInfo> {
-    use std::process::Command;
-    use std::fs;
-    
-    let mut gpus = Vec::new();
-    
-    // Try lspci first
-    if let Ok(output) = Command::new("lspci").output() {
-        if let Ok(output_str) = String::from_utf8(output.stdout) {
-            for line in output_str.lines() {
-                if line.contains("VGA") || line.contains("3D") || line.contains("Display") {
-                    let name = line.split(':').skip(2).collect::<Vec<_>>().join(":").trim().to_string();
-                    if !name.is_empty() {
-                        gpus.push(GpuInfo {
-                            name,
-                            ram: None,
-                            driver_version: None,
-                            vram_usage: None,
-                        });
-                    }
-                }
-            }
-        }
-    }
-    
-    // Try to get VRAM info from /sys for NVIDIA/AMD
-    if let Ok(entries) = fs::read_dir("/sys/class/drm") {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if let Some(name) = path.file_name() {
-                let name_str = name.to_string_lossy();
-                if name_str.starts_with("card") && !name_str.contains('-') {
-                    // Try to read memory info
-                    let mem_path = path.join("device/mem_info_vram_total");
-                    if let Ok(mem_str) = fs::read_to_string(mem_path) {
-                        if let Ok(mem_bytes) = mem_str.trim().parse::<u64>() {
-                            if let Some(gpu) = gpus.first_mut() {
-                                gpu.ram = Some((mem_bytes / 1024 / 1024) as u32);
-                            }
-                        }
-                    }
-                }
+    use std::path::Path;
+
+    let mut gpus: Vec<GpuInfo> = drm::discover_cards(Path::new(drm::DRM_ROOT))
+        .into_iter()
+        .map(|card| {
+            let vendor = pci::vendor_name(card.vendor_id).unwrap_or_else(|| "Unknown vendor".to_string());
+            let name = match pci::device_name(card.vendor_id, card.device_id) {
+                Some(device) => format!("{} {}", vendor, device),
+                None => format!("{} GPU [{:04x}:{:04x}]", vendor, card.vendor_id, card.device_id),
+            };
+            let stats = card.stats;
+            GpuInfo {
+                name,
+                ram: stats.vram_total.map(|total| (total / 1024 / 1024) as u32),
+                driver_version: card.driver.as_deref().and_then(drm::driver_version),
+                vram_usage: stats.vram_used,
+                utilization: stats.utilization,
+                pci_slot: Some(card.pci_slot),
+                vendor_id: Some(format!("{:04x}", card.vendor_id)),
+                device_id: Some(format!("{:04x}", card.device_id)),
+                driver: card.driver,
+                core_clock_mhz: stats.core_clock_mhz,
+                max_core_clock_mhz: stats.max_core_clock_mhz,
+                memory_clock_mhz: stats.memory_clock_mhz,
+                temperature: stats.temperature,
             }
-        }
-    }
-    
+        })
+        .collect();
+
     if gpus.is_empty() {
         gpus.push(GpuInfo {
             name: "GPU".to_string(),
             ram: None,
             driver_version: None,
             vram_usage: None,
+            ..Default::default()
         });
     }
-    
+
     gpus
 }
 
+// Per-logical-CPU usage and clocks; offline cores only show up on Linux
+fn build_per_core(sys: &System) -> Vec<CoreInfo> {
+    let cores: Vec<CoreInfo> = sys.cpus().iter().enumerate().map(|(i, cpu)| {
+        // sysinfo names Linux cores "cpu0", "cpu1", ... and skips offline ones
+        let index = cpu.name()
+            .trim_start_matches("cpu")
+            .parse::<usize>()
+            .unwrap_or(i);
+        CoreInfo {
+            index,
+            usage: Some(cpu.cpu_usage()),
+            frequency: Some(cpu.frequency()),
+            min_frequency: None,
+            max_frequency: None,
+            online: true,
+        }
+    }).collect();
+
+    #[cfg(target_os = "linux")]
+    let cores = merge_core_freqs_linux(cores);
+
+    cores
+}
+
+#[cfg(target_os = "linux")]
+fn merge_core_freqs_linux(mut cores: Vec<CoreInfo>) -> Vec<CoreInfo> {
+    for (index, freq) in fetch_core_freqs_linux() {
+        match cores.iter_mut().find(|c| c.index == index) {
+            Some(core) => {
+                core.frequency = freq.current.or(core.frequency);
+                core.min_frequency = freq.min;
+                core.max_frequency = freq.max;
+                core.online = freq.online;
+            }
+            None => cores.push(CoreInfo {
+                index,
+                usage: None,
+                frequency: freq.current,
+                min_frequency: freq.min,
+                max_frequency: freq.max,
+                online: freq.online,
+            }),
+        }
+    }
+    cores.sort_by_key(|c| c.index);
+    cores
+}
+
+#[cfg(target_os = "linux")]
+struct CoreFreq {
+    current: Option<u64>,
+    min: Option<u64>,
+    max: Option<u64>,
+    online: bool,
+}
+
+// Linux per-core cpufreq scaling values (sysfs reports kHz)
+#[cfg(target_os = "linux")]
+fn fetch_core_freqs_linux() -> Vec<(usize, CoreFreq)> {
+    use std::fs;
+    use std::path::Path;
+
+    fn read_mhz(path: &Path) -> Option<u64> {
+        fs::read_to_string(path).ok()?.trim().parse::<u64>().ok().map(|khz| khz / 1000)
+    }
+
+    let mut cores = Vec::new();
+    if let Ok(entries) = fs::read_dir("/sys/devices/system/cpu") {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(index) = name.strip_prefix("cpu").and_then(|n| n.parse::<usize>().ok()) else {
+                continue;
+            };
+            let path = entry.path();
+            // cpu0 usually cannot be hot-unplugged and has no "online" file
+            let online = fs::read_to_string(path.join("online"))
+                .map(|s| s.trim() != "0")
+                .unwrap_or(true);
+            let cpufreq = path.join("cpufreq");
+            cores.push((index, CoreFreq {
+                current: read_mhz(&cpufreq.join("scaling_cur_freq")),
+                min: read_mhz(&cpufreq.join("scaling_min_freq")),
+                max: read_mhz(&cpufreq.join("scaling_max_freq")),
+                online,
+            }));
+        }
+    }
+    cores
+}
+
 // macOS CPU temperature
 #[cfg(target_os = "macos")]
 fn fetch_cpu_temp_macos() -> Option<f32> {
@@ -1050,30 +1552,6 @@ fn fetch_cpu_temp_macos() -> Option<f32> {
     None
 }
 
-// Linux CPU temperature
-#[cfg(target_os = "linux")]
-fn fetch_cpu_temp_linux() -> Option<f32> {
-    use std::fs;
-    
-    // Try common thermal zones
-    let thermal_paths = vec![
-        "/sys/class/thermal/thermal_zone0/temp",
-        "/sys/class/thermal/thermal_zone1/temp",
-        "/sys/class/hwmon/hwmon0/temp1_input",
-        "/sys/class/hwmon/hwmon1/temp1_input",
-    ];
-    
-    for path in thermal_paths {
-        if let Ok(temp_str) = fs::read_to_string(path) {
-            if let Ok(temp_millidegrees) = temp_str.trim().parse::<i32>() {
-                return Some(temp_millidegrees as f32 / 1000.0);
-            }
-        }
-    }
-    
-    None
-}
-
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 fn main() {
     let logger_state = Arc::new(Mutex::new(PerformanceLoggerState {
@@ -1082,17 +1560,77 @@ fn main() {
         handle: None,
     }));
 
+    let protection_state: ProtectionState = Arc::new(Mutex::new(ProtectionPolicy::default()));
+    let sampler_state: SamplerState = Arc::new(Mutex::new(Sampler::new(
+        SamplerConfig::default(),
+        Arc::clone(&protection_state),
+    )));
+
+    let stream_state: StreamState = Arc::new(Mutex::new(StreamHub::new()));
+
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .manage(logger_state)
+        .manage(Arc::clone(&sampler_state))
+        .manage(Arc::clone(&stream_state))
+        .manage(protection_state)
+        .manage::<IconCacheState>(Arc::new(Mutex::new(IconCache::new())))
+        .setup(move |app| {
+            // Load protection and watchdog rules before the sampler starts evaluating them.
+            // Failed loads are reported through fetch_protection_status and fetch_watchdog_status.
+            let config_dir = app.path().app_config_dir()?;
+            let _ = app.state::<ProtectionState>().lock().unwrap().load(config_dir.join("protection.json"));
+            let _ = sampler_state.lock().unwrap().watchdog.load(config_dir.join("watchdog.json"));
+            let icon_dir = app.path().app_cache_dir()?.join("icons");
+            app.state::<IconCacheState>().lock().unwrap().set_dir(icon_dir);
+            sampler::spawn(Arc::clone(&sampler_state));
+            stream::spawn(app.handle().clone(), stream_state, sampler_state);
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                window.state::<StreamState>().lock().unwrap().remove_target(window.label());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             get_username,
             fetch_system_overview,
             fetch_processes,
+            fetch_process_tree,
+            fetch_process_icon,
+            fetch_process_details,
+            fetch_process_groups,
             fetch_network_info,
+            fetch_network_history,
+            fetch_connections,
             end_process,
+            suspend_process,
+            resume_process,
+            set_process_priority,
+            set_io_priority,
+            set_cpu_affinity,
+            get_process_scheduling,
+            get_protection_rules,
+            set_protection_rules,
+            reset_protection_rules,
+            fetch_protection_status,
+            get_watchdog_rules,
+            set_watchdog_rules,
+            fetch_watchdog_history,
+            fetch_watchdog_status,
+            clear_watchdog_history,
+            check_alerts,
+            fetch_disk_io,
+            fetch_top_io_processes,
+            fetch_sensors,
+            fetch_pci_devices,
+            get_sampler_config,
+            set_sampler_config,
+            subscribe_metrics,
+            unsubscribe_metrics,
+            list_metric_subscriptions,
             get_disk_health,
             clean_storage,
             fetch_system_logs,
@@ -1133,7 +1671,7 @@ fn fetch_gpus_dxgi() -> Vec<GpuInfo> {
                             .cloned()
                             .collect::<Vec<u16>>(),
                     );
-                    let ram = Some(desc.DedicatedVideoMemory as u32);
+                    let ram = Some((desc.DedicatedVideoMemory / 1024 / 1024) as u32);
 
                     let mut vram_usage: Option<u64> = None;
                     if let Ok(adapter3) = adapter.cast::<IDXGIAdapter3>() {
@@ -1152,6 +1690,7 @@ fn fetch_gpus_dxgi() -> Vec<GpuInfo> {
                         ram,
                         driver_version: None,
                         vram_usage,
+                        ..Default::default()
                     });
                 }
                 i += 1;
@@ -0,0 +1,172 @@
+use std::fs;
+use std::path::Path;
+
+// Interfaces are read from <root>/sys/class/net and VLANs from <root>/proc/net/vlan
+pub const FS_ROOT: &str = "/";
+
+// Link-layer details of one interface from /sys/class/net/<if>
+#[derive(Default, Debug)]
+pub struct LinkInfo {
+    pub mac_address: Option<String>,
+    pub interface_type: Option<String>,
+    pub link_speed_mbps: Option<u32>,
+    pub operstate: Option<String>,
+    pub carrier: Option<bool>,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    read_trimmed(path)?.parse().ok()
+}
+
+pub fn read_link_info(root: &Path, name: &str) -> LinkInfo {
+    let dir = root.join("sys/class/net").join(name);
+    LinkInfo {
+        // Point-to-point and tunnel devices report an all-zero or empty address
+        mac_address: read_trimmed(&dir.join("address"))
+            .filter(|mac| mac.chars().any(|c| c != '0' && c != ':')),
+        interface_type: Some(classify(root, &dir)),
+        // "speed" is -1 or unreadable (EINVAL) while the link is down or for virtual devices
+        link_speed_mbps: read_trimmed(&dir.join("speed"))
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|&s| s > 0)
+            .map(|s| s as u32),
+        operstate: read_trimmed(&dir.join("operstate")),
+        carrier: read_trimmed(&dir.join("carrier")).map(|c| c == "1"),
+        rx_dropped: read_u64(&dir.join("statistics/rx_dropped")).unwrap_or(0),
+        tx_dropped: read_u64(&dir.join("statistics/tx_dropped")).unwrap_or(0),
+    }
+}
+
+// ARPHRD_* values from <linux/if_arp.h> that matter for classification
+const ARPHRD_ETHER: u32 = 1;
+const ARPHRD_LOOPBACK: u32 = 772;
+const ARPHRD_NONE: u32 = 65534;
+
+fn classify(root: &Path, dir: &Path) -> String {
+    let arp_type = read_u64(&dir.join("type")).map(|t| t as u32);
+    let driver = fs::read_link(dir.join("device/driver"))
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()));
+    // Devices without a parent bus live under /sys/devices/virtual/net
+    let is_virtual = fs::canonicalize(dir)
+        .map(|p| p.to_string_lossy().contains("/devices/virtual/"))
+        .unwrap_or(false);
+
+    let kind = if arp_type == Some(ARPHRD_LOOPBACK) {
+        "loopback"
+    } else if dir.join("wireless").exists() || dir.join("phy80211").exists() {
+        "wireless"
+    } else if dir.join("bridge").exists() {
+        "bridge"
+    } else if dir.join("bonding").exists() {
+        "bond"
+    } else if dir.join("tun_flags").exists() {
+        "tun"
+    } else if arp_type == Some(ARPHRD_NONE) {
+        "tunnel"
+    } else if is_virtual {
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with("veth") {
+            "veth"
+        } else if name.contains('.') || root.join("proc/net/vlan").join(name.as_ref()).exists() {
+            "vlan"
+        } else {
+            "virtual"
+        }
+    } else if driver.as_deref() == Some("cdc_ether") || driver.as_deref() == Some("rndis_host") {
+        "usb"
+    } else if arp_type == Some(ARPHRD_ETHER) {
+        "ethernet"
+    } else {
+        "other"
+    };
+    kind.to_string()
+}
+
+// Display status from the kernel's RFC 2863 operational state. Loopback and many
+// tunnels report "unknown", so fall back to the carrier flag for those.
+pub fn status_from_operstate(info: &LinkInfo) -> String {
+    match info.operstate.as_deref() {
+        Some("up") => "Connected",
+        Some("dormant") => "Dormant",
+        Some("testing") => "Testing",
+        Some("unknown") if info.carrier == Some(true) => "Connected",
+        Some(_) => "Disconnected",
+        None => "Unknown",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/netdev")
+    }
+
+    fn kind(name: &str) -> String {
+        read_link_info(&fixture_root(), name).interface_type.unwrap()
+    }
+
+    #[test]
+    fn reads_link_details() {
+        let eth = read_link_info(&fixture_root(), "enp3s0");
+        assert_eq!(eth.mac_address.as_deref(), Some("9c:6b:00:12:34:56"));
+        assert_eq!(eth.link_speed_mbps, Some(1000));
+        assert_eq!(eth.operstate.as_deref(), Some("up"));
+        assert_eq!(eth.carrier, Some(true));
+        assert_eq!((eth.rx_dropped, eth.tx_dropped), (12, 3));
+
+        // All-zero address, no speed file and a link that is down (-1)
+        let lo = read_link_info(&fixture_root(), "lo");
+        assert_eq!(lo.mac_address, None);
+        assert_eq!(lo.link_speed_mbps, None);
+        assert_eq!(read_link_info(&fixture_root(), "usb0").link_speed_mbps, None);
+
+        let missing = read_link_info(&fixture_root(), "eth9");
+        assert_eq!(missing.operstate, None);
+        assert_eq!(missing.rx_dropped, 0);
+    }
+
+    #[test]
+    fn classifies_interfaces() {
+        assert_eq!(kind("lo"), "loopback");
+        assert_eq!(kind("enp3s0"), "ethernet");
+        assert_eq!(kind("wlp2s0"), "wireless");
+        assert_eq!(kind("usb0"), "usb");
+        assert_eq!(kind("docker0"), "bridge");
+        assert_eq!(kind("tun0"), "tun");
+        assert_eq!(kind("wg0"), "tunnel");
+        assert_eq!(kind("veth3f2a1b"), "veth");
+        assert_eq!(kind("eth0.20"), "vlan");
+        // No dot in the name; only /proc/net/vlan identifies it
+        assert_eq!(kind("trunk100"), "vlan");
+        assert_eq!(kind("dummy0"), "virtual");
+    }
+
+    #[test]
+    fn status_follows_operstate_then_carrier() {
+        let status = |name: &str| status_from_operstate(&read_link_info(&fixture_root(), name));
+        assert_eq!(status("enp3s0"), "Connected");
+        assert_eq!(status("wlp2s0"), "Dormant");
+        assert_eq!(status("docker0"), "Disconnected");
+        // Loopback reports "unknown" but has carrier
+        assert_eq!(status("lo"), "Connected");
+        assert_eq!(status("dummy0"), "Unknown");
+
+        let info = LinkInfo {
+            operstate: Some("unknown".to_string()),
+            carrier: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(status_from_operstate(&info), "Disconnected");
+    }
+}
@@ -0,0 +1,242 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::{NetworkExt, System, SystemExt};
+
+// How much per-interface rate history is kept in memory
+pub const HISTORY_WINDOW: Duration = Duration::from_secs(300);
+
+// Cumulative counters for one interface at one point in time
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetCounters {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct NetRate {
+    pub timestamp_unix_ms: u64,
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
+    pub rx_packets_per_sec: f64,
+    pub tx_packets_per_sec: f64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct NetHistory {
+    pub interface: String,
+    pub samples: Vec<NetRate>,
+}
+
+// sysinfo reads 64-bit counters on Linux and Windows; macOS's if_data only has
+// 32-bit ones, which wrap after 4 GiB
+#[cfg(target_os = "macos")]
+const COUNTER_IS_32_BIT: bool = true;
+#[cfg(not(target_os = "macos"))]
+const COUNTER_IS_32_BIT: bool = false;
+
+// Increase of a cumulative counter between two readings. A step backwards is a
+// reset (driver reload, interface re-created) and the new value is what accrued
+// since, unless the counter is 32-bit and the step looks like a wrap.
+fn counter_delta(prev: u64, cur: u64, is_32_bit: bool) -> u64 {
+    if cur >= prev {
+        return cur - prev;
+    }
+    let max32 = u32::MAX as u64;
+    if is_32_bit && prev <= max32 {
+        let wrapped = max32 - prev + cur + 1;
+        // A real wrap only crosses a small part of the range within one interval
+        if wrapped <= max32 / 2 {
+            return wrapped;
+        }
+    }
+    cur
+}
+
+pub fn compute_rate(prev: &NetCounters, cur: &NetCounters, elapsed: Duration, timestamp_unix_ms: u64) -> NetRate {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return NetRate {
+            timestamp_unix_ms,
+            ..Default::default()
+        };
+    }
+    let per_sec = |p: u64, c: u64| counter_delta(p, c, COUNTER_IS_32_BIT) as f64 / secs;
+
+    NetRate {
+        timestamp_unix_ms,
+        rx_bytes_per_sec: per_sec(prev.rx_bytes, cur.rx_bytes),
+        tx_bytes_per_sec: per_sec(prev.tx_bytes, cur.tx_bytes),
+        rx_packets_per_sec: per_sec(prev.rx_packets, cur.rx_packets),
+        tx_packets_per_sec: per_sec(prev.tx_packets, cur.tx_packets),
+    }
+}
+
+struct InterfaceState {
+    counters: NetCounters,
+    history: VecDeque<(Instant, NetRate)>,
+}
+
+// Keeps the previous counters of every interface so rates can be derived on each
+// refresh, plus a rolling window of past rates
+pub struct NetRateTracker {
+    interfaces: HashMap<String, InterfaceState>,
+    prev_at: Option<Instant>,
+    window: Duration,
+}
+
+impl NetRateTracker {
+    pub fn new() -> Self {
+        NetRateTracker {
+            interfaces: HashMap::new(),
+            prev_at: None,
+            window: HISTORY_WINDOW,
+        }
+    }
+
+    pub fn update(&mut self, sys: &System) {
+        let samples: Vec<(String, NetCounters)> = sys
+            .networks()
+            .into_iter()
+            .map(|(name, data)| {
+                let counters = NetCounters {
+                    rx_bytes: data.total_received(),
+                    tx_bytes: data.total_transmitted(),
+                    rx_packets: data.total_packets_received(),
+                    tx_packets: data.total_packets_transmitted(),
+                };
+                (name.clone(), counters)
+            })
+            .collect();
+        let unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        self.update_from(&samples, Instant::now(), unix_ms);
+    }
+
+    fn update_from(&mut self, samples: &[(String, NetCounters)], now: Instant, unix_ms: u64) {
+        let elapsed = self.prev_at.map(|t| now.duration_since(t));
+        let mut interfaces = HashMap::with_capacity(samples.len());
+
+        for (name, cur) in samples {
+            // Interfaces that disappeared are dropped along with their history; new
+            // ones only get a rate once there is a previous reading to compare with
+            let mut state = match (self.interfaces.remove(name), elapsed) {
+                (Some(mut state), Some(elapsed)) => {
+                    state.history.push_back((now, compute_rate(&state.counters, cur, elapsed, unix_ms)));
+                    state
+                }
+                _ => InterfaceState {
+                    counters: *cur,
+                    history: VecDeque::new(),
+                },
+            };
+            while state.history.front().is_some_and(|(t, _)| now.duration_since(*t) > self.window) {
+                state.history.pop_front();
+            }
+            state.counters = *cur;
+            interfaces.insert(name.clone(), state);
+        }
+
+        self.interfaces = interfaces;
+        self.prev_at = Some(now);
+    }
+
+    pub fn rate_for(&self, interface: &str) -> Option<&NetRate> {
+        self.interfaces.get(interface)?.history.back().map(|(_, rate)| rate)
+    }
+
+    // Rates from the last `span` (capped at the history window), oldest first
+    pub fn history(&self, interface: Option<&str>, span: Duration) -> Vec<NetHistory> {
+        let now = Instant::now();
+        let mut result: Vec<NetHistory> = self
+            .interfaces
+            .iter()
+            .filter(|(name, _)| interface.is_none() || interface == Some(name.as_str()))
+            .map(|(name, state)| NetHistory {
+                interface: name.clone(),
+                samples: state
+                    .history
+                    .iter()
+                    .filter(|(t, _)| now.duration_since(*t) <= span)
+                    .map(|(_, rate)| rate.clone())
+                    .collect(),
+            })
+            .collect();
+        result.sort_by(|a, b| a.interface.cmp(&b.interface));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counters(rx_bytes: u64, tx_bytes: u64, rx_packets: u64, tx_packets: u64) -> NetCounters {
+        NetCounters {
+            rx_bytes,
+            tx_bytes,
+            rx_packets,
+            tx_packets,
+        }
+    }
+
+    #[test]
+    fn computes_per_second_rates() {
+        let prev = counters(1_000, 500, 10, 5);
+        let cur = counters(3_000, 1_500, 30, 15);
+        let rate = compute_rate(&prev, &cur, Duration::from_secs(2), 42);
+        assert_eq!(rate.timestamp_unix_ms, 42);
+        assert_eq!(rate.rx_bytes_per_sec, 1_000.0);
+        assert_eq!(rate.tx_bytes_per_sec, 500.0);
+        assert_eq!(rate.rx_packets_per_sec, 10.0);
+        assert_eq!(rate.tx_packets_per_sec, 5.0);
+    }
+
+    #[test]
+    fn handles_32_bit_wrap_and_reset() {
+        // 32-bit counter rolled over: 16 bytes before the top plus 84 after
+        assert_eq!(counter_delta(u32::MAX as u64 - 15, 84, true), 100);
+        // Interface re-created: counting restarts from zero
+        assert_eq!(counter_delta(5_000_000_000, 1_200, true), 1_200);
+        assert_eq!(counter_delta(1_000, 200, true), 200);
+    }
+
+    #[test]
+    fn a_64_bit_counter_going_backwards_is_always_a_reset() {
+        // prev in 2^31..2^32 would look like a wrap if the counter were 32-bit
+        assert_eq!(counter_delta(3_000_000_000, 1_000, false), 1_000);
+        assert_eq!(counter_delta(u32::MAX as u64 - 15, 84, false), 84);
+        assert_eq!(counter_delta(5_000_000_000, 1_200, false), 1_200);
+    }
+
+    #[test]
+    fn keeps_rolling_history_and_drops_vanished_interfaces() {
+        let mut tracker = NetRateTracker::new();
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        tracker.update_from(&[("eth0".to_string(), counters(0, 0, 0, 0))], at(0), 0);
+        assert!(tracker.rate_for("eth0").is_none());
+
+        for i in 1..=400u64 {
+            let samples = [
+                ("eth0".to_string(), counters(i * 1_000, i * 100, i, i)),
+                ("wlan0".to_string(), counters(0, 0, 0, 0)),
+            ];
+            tracker.update_from(&samples, at(i), i * 1_000);
+        }
+        assert_eq!(tracker.rate_for("eth0").unwrap().rx_bytes_per_sec, 1_000.0);
+
+        let eth0 = &tracker.interfaces["eth0"].history;
+        assert_eq!(eth0.len(), HISTORY_WINDOW.as_secs() as usize + 1);
+        assert_eq!(eth0.back().unwrap().1.timestamp_unix_ms, 400_000);
+
+        tracker.update_from(&[("wlan0".to_string(), counters(0, 0, 0, 0))], at(401), 401_000);
+        assert!(tracker.rate_for("eth0").is_none());
+        assert_eq!(tracker.interfaces.len(), 1);
+    }
+}
@@ -0,0 +1,349 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub const PCI_DEVICES_ROOT: &str = "/sys/bus/pci/devices";
+
+// Where distributions install the pciutils/hwdata database
+const PCI_IDS_PATHS: [&str; 4] = [
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pci.ids",
+    "/usr/local/share/pci.ids",
+];
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PciDevice {
+    slot: String, // domain:bus:device.function, e.g. "0000:03:00.0"
+    vendor_id: String,
+    device_id: String,
+    subsystem_vendor_id: Option<String>,
+    subsystem_device_id: Option<String>,
+    class_code: String, // class, subclass and prog-if as six hex digits
+    vendor_name: Option<String>,
+    device_name: Option<String>,
+    class_name: Option<String>,
+    subclass_name: Option<String>,
+    driver: Option<String>,
+    current_link_speed: Option<String>, // e.g. "16.0 GT/s PCIe"
+    current_link_width: Option<u32>,
+    max_link_speed: Option<String>,
+    max_link_width: Option<u32>,
+    iommu_group: Option<u32>,
+}
+
+#[derive(Default)]
+struct VendorEntry {
+    name: String,
+    devices: HashMap<u16, String>,
+}
+
+#[derive(Default)]
+struct ClassEntry {
+    name: String,
+    subclasses: HashMap<u8, String>,
+}
+
+// Vendor/device and class/subclass names from a pci.ids file; subsystem and
+// prog-if lines are not needed and are skipped
+#[derive(Default)]
+pub struct PciIds {
+    vendors: HashMap<u16, VendorEntry>,
+    classes: HashMap<u8, ClassEntry>,
+}
+
+impl PciIds {
+    pub fn parse(text: &str) -> Self {
+        enum Section {
+            Vendor(u16),
+            Class(u8),
+            Other,
+        }
+
+        let mut ids = PciIds::default();
+        let mut section = Section::Other;
+
+        for line in text.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let depth = line.chars().take_while(|&c| c == '\t').count();
+            let body = &line[depth..];
+
+            match depth {
+                0 => {
+                    section = if let Some(rest) = body.strip_prefix("C ") {
+                        match split_id(rest).and_then(|(id, name)| Some((u8::from_str_radix(id, 16).ok()?, name))) {
+                            Some((class, name)) => {
+                                ids.classes.entry(class).or_default().name = name.to_string();
+                                Section::Class(class)
+                            }
+                            None => Section::Other,
+                        }
+                    } else {
+                        match split_id(body).and_then(|(id, name)| Some((u16::from_str_radix(id, 16).ok()?, name))) {
+                            Some((vendor, name)) => {
+                                ids.vendors.entry(vendor).or_default().name = name.to_string();
+                                Section::Vendor(vendor)
+                            }
+                            None => Section::Other,
+                        }
+                    };
+                }
+                1 => {
+                    let Some((id, name)) = split_id(body) else {
+                        continue;
+                    };
+                    match section {
+                        Section::Vendor(vendor) => {
+                            if let Ok(device) = u16::from_str_radix(id, 16) {
+                                if let Some(entry) = ids.vendors.get_mut(&vendor) {
+                                    entry.devices.insert(device, name.to_string());
+                                }
+                            }
+                        }
+                        Section::Class(class) => {
+                            if let Ok(subclass) = u8::from_str_radix(id, 16) {
+                                if let Some(entry) = ids.classes.get_mut(&class) {
+                                    entry.subclasses.insert(subclass, name.to_string());
+                                }
+                            }
+                        }
+                        Section::Other => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        ids
+    }
+
+    pub fn vendor_name(&self, vendor: u16) -> Option<&str> {
+        self.vendors.get(&vendor).map(|v| v.name.as_str())
+    }
+
+    pub fn device_name(&self, vendor: u16, device: u16) -> Option<&str> {
+        self.vendors.get(&vendor)?.devices.get(&device).map(String::as_str)
+    }
+
+    fn class_name(&self, class: u8) -> Option<&str> {
+        self.classes.get(&class).map(|c| c.name.as_str())
+    }
+
+    fn subclass_name(&self, class: u8, subclass: u8) -> Option<&str> {
+        self.classes.get(&class)?.subclasses.get(&subclass).map(String::as_str)
+    }
+}
+
+// "1002  Advanced Micro Devices, Inc. [AMD/ATI]" -> ("1002", "Advanced Micro ...")
+fn split_id(body: &str) -> Option<(&str, &str)> {
+    let (id, name) = body.split_once(char::is_whitespace)?;
+    Some((id, name.trim()))
+}
+
+// Loaded once on first use; empty when no database is installed
+pub fn database() -> &'static PciIds {
+    static DATABASE: OnceLock<PciIds> = OnceLock::new();
+    DATABASE.get_or_init(|| {
+        PCI_IDS_PATHS
+            .iter()
+            .find_map(|path| fs::read_to_string(path).ok())
+            .map(|text| PciIds::parse(&text))
+            .unwrap_or_default()
+    })
+}
+
+// Common vendors, used when no pci.ids database is installed
+fn builtin_vendor_name(vendor: u16) -> Option<&'static str> {
+    Some(match vendor {
+        0x1002 => "AMD",
+        0x10de => "NVIDIA",
+        0x8086 => "Intel",
+        0x144d => "Samsung",
+        0x10ec => "Realtek",
+        0x14e4 => "Broadcom",
+        0x1af4 => "Red Hat",
+        0x15ad => "VMware",
+        0x1234 => "QEMU",
+        0x80ee => "VirtualBox",
+        0x1a03 => "ASPEED",
+        0x102b => "Matrox",
+        _ => return None,
+    })
+}
+
+pub fn vendor_name(vendor: u16) -> Option<String> {
+    database()
+        .vendor_name(vendor)
+        .or_else(|| builtin_vendor_name(vendor))
+        .map(str::to_string)
+}
+
+pub fn device_name(vendor: u16, device: u16) -> Option<String> {
+    database().device_name(vendor, device).map(str::to_string)
+}
+
+// Base class names are fixed by the PCI spec, so they are known even without pci.ids
+fn builtin_class_name(class: u8) -> Option<&'static str> {
+    Some(match class {
+        0x00 => "Unclassified device",
+        0x01 => "Mass storage controller",
+        0x02 => "Network controller",
+        0x03 => "Display controller",
+        0x04 => "Multimedia controller",
+        0x05 => "Memory controller",
+        0x06 => "Bridge",
+        0x07 => "Communication controller",
+        0x08 => "Generic system peripheral",
+        0x09 => "Input device controller",
+        0x0a => "Docking station",
+        0x0b => "Processor",
+        0x0c => "Serial bus controller",
+        0x0d => "Wireless controller",
+        0x0e => "Intelligent controller",
+        0x0f => "Satellite communications controller",
+        0x10 => "Encryption controller",
+        0x11 => "Signal processing controller",
+        0x12 => "Processing accelerators",
+        0x13 => "Non-Essential Instrumentation",
+        0x40 => "Coprocessor",
+        0xff => "Unassigned class",
+        _ => return None,
+    })
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn read_hex(path: &Path) -> Option<u32> {
+    u32::from_str_radix(read_trimmed(path)?.trim_start_matches("0x"), 16).ok()
+}
+
+fn link_name(path: &Path) -> Option<String> {
+    let target = fs::read_link(path).ok()?;
+    Some(target.file_name()?.to_string_lossy().into_owned())
+}
+
+pub fn list_devices(root: &Path) -> Vec<PciDevice> {
+    let ids = database();
+    let mut devices = Vec::new();
+    let Ok(entries) = fs::read_dir(root) else {
+        return devices;
+    };
+
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        let (Some(vendor), Some(device), Some(class_code)) = (
+            read_hex(&dir.join("vendor")),
+            read_hex(&dir.join("device")),
+            read_hex(&dir.join("class")),
+        ) else {
+            continue;
+        };
+        let (vendor, device) = (vendor as u16, device as u16);
+        let class = (class_code >> 16) as u8;
+        let subclass = (class_code >> 8) as u8;
+
+        devices.push(PciDevice {
+            slot: entry.file_name().to_string_lossy().into_owned(),
+            vendor_id: format!("{:04x}", vendor),
+            device_id: format!("{:04x}", device),
+            subsystem_vendor_id: read_hex(&dir.join("subsystem_vendor")).map(|v| format!("{:04x}", v)),
+            subsystem_device_id: read_hex(&dir.join("subsystem_device")).map(|v| format!("{:04x}", v)),
+            class_code: format!("{:06x}", class_code),
+            vendor_name: vendor_name(vendor),
+            device_name: device_name(vendor, device),
+            class_name: ids
+                .class_name(class)
+                .or_else(|| builtin_class_name(class))
+                .map(str::to_string),
+            subclass_name: ids.subclass_name(class, subclass).map(str::to_string),
+            driver: link_name(&dir.join("driver")),
+            // Link attributes only exist on PCIe functions; "Unknown" means no link trained
+            current_link_speed: read_trimmed(&dir.join("current_link_speed")).filter(|s| !s.starts_with("Unknown")),
+            current_link_width: read_trimmed(&dir.join("current_link_width")).and_then(|w| w.parse().ok()),
+            max_link_speed: read_trimmed(&dir.join("max_link_speed")).filter(|s| !s.starts_with("Unknown")),
+            max_link_width: read_trimmed(&dir.join("max_link_width")).and_then(|w| w.parse().ok()),
+            iommu_group: link_name(&dir.join("iommu_group")).and_then(|g| g.parse().ok()),
+        });
+    }
+
+    devices.sort_by(|a, b| a.slot.cmp(&b.slot));
+    devices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PCI_IDS: &str = include_str!("../tests/fixtures/pci.ids");
+
+    #[test]
+    fn resolves_vendor_and_device_names() {
+        let ids = PciIds::parse(PCI_IDS);
+        assert_eq!(ids.vendor_name(0x1002), Some("Advanced Micro Devices, Inc. [AMD/ATI]"));
+        assert_eq!(
+            ids.device_name(0x1002, 0x73bf),
+            Some("Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]")
+        );
+        assert_eq!(ids.device_name(0x8086, 0x46a6), Some("Alder Lake-P GT2 [Iris Xe Graphics]"));
+        // Subsystem lines must not be mistaken for devices
+        assert_eq!(ids.device_name(0x1002, 0x1458), None);
+        assert_eq!(ids.vendor_name(0xdead), None);
+    }
+
+    #[test]
+    fn resolves_class_and_subclass_names() {
+        let ids = PciIds::parse(PCI_IDS);
+        assert_eq!(ids.class_name(0x03), Some("Display controller"));
+        assert_eq!(ids.subclass_name(0x03, 0x00), Some("VGA compatible controller"));
+        assert_eq!(ids.subclass_name(0x01, 0x08), Some("Non-Volatile memory controller"));
+        // Prog-if lines are ignored
+        assert_eq!(ids.subclass_name(0x01, 0x02), None);
+    }
+
+    #[test]
+    fn builtin_classes_cover_missing_database() {
+        let ids = PciIds::parse("");
+        assert_eq!(ids.class_name(0x02), None);
+        assert_eq!(builtin_class_name(0x02), Some("Network controller"));
+        assert_eq!(builtin_class_name(0x42), None);
+    }
+
+    #[test]
+    fn lists_devices_from_sysfs() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs/bus/pci/devices");
+        let devices = list_devices(&root);
+        let slots: Vec<&str> = devices.iter().map(|d| d.slot.as_str()).collect();
+        assert_eq!(slots, vec!["0000:00:01.0", "0000:00:08.1", "0000:00:14.3", "0000:03:00.0"]);
+
+        let gpu = &devices[3];
+        assert_eq!((gpu.vendor_id.as_str(), gpu.device_id.as_str()), ("1002", "73bf"));
+        assert_eq!(gpu.subsystem_vendor_id.as_deref(), Some("1458"));
+        assert_eq!(gpu.subsystem_device_id.as_deref(), Some("2408"));
+        assert_eq!(gpu.class_code, "030000");
+        assert_eq!(gpu.class_name.as_deref(), Some("Display controller"));
+        assert_eq!(gpu.driver.as_deref(), Some("amdgpu"));
+        assert_eq!(gpu.current_link_speed.as_deref(), Some("8.0 GT/s PCIe"));
+        assert_eq!(gpu.current_link_width, Some(16));
+        assert_eq!(gpu.max_link_speed.as_deref(), Some("16.0 GT/s PCIe"));
+        assert_eq!(gpu.iommu_group, Some(14));
+
+        // A root port with nothing plugged in has no trained link
+        let idle_port = &devices[1];
+        assert_eq!(idle_port.current_link_speed, None);
+        assert_eq!(idle_port.max_link_speed.as_deref(), Some("8.0 GT/s PCIe"));
+        assert_eq!(idle_port.driver, None);
+        assert_eq!(idle_port.iommu_group, None);
+
+        // Conventional PCI functions have no link attributes at all
+        let isa = &devices[2];
+        assert_eq!(isa.current_link_speed, None);
+        assert_eq!(isa.max_link_width, None);
+        assert_eq!(isa.subsystem_vendor_id, None);
+    }
+}
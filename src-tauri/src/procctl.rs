@@ -0,0 +1,624 @@
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How often a signalled process is checked for exit
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// How long to wait for SIGKILL to take effect before giving up
+const KILL_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminationOutcome {
+    Terminated,       // exited within the grace period after SIGTERM
+    Killed,           // needed SIGKILL
+    PermissionDenied, // owned by another user or otherwise protected by the OS
+    NotFound,         // no such process (possibly already exited)
+    StillRunning,     // survived SIGKILL, e.g. stuck in uninterruptible sleep
+}
+
+#[derive(Serialize, Debug)]
+pub struct TerminationResult {
+    pub pid: i32,
+    pub outcome: TerminationOutcome,
+    pub elapsed_ms: u64,
+}
+
+fn wait_for_exit(pid: i32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !is_alive(pid) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+// What send_term delivered: where no polite signal exists it kills outright
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TermSent {
+    Term,
+    #[cfg_attr(unix, allow(dead_code))]
+    Kill,
+}
+
+// SIGTERM, then SIGKILL once `grace` has passed without the process exiting
+pub fn terminate(pid: i32, grace: Duration) -> TerminationResult {
+    let started = Instant::now();
+    let outcome = escalate(pid, grace, send_term, send_kill, wait_for_exit);
+    TerminationResult {
+        pid,
+        outcome,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+fn escalate(
+    pid: i32,
+    grace: Duration,
+    send_term: impl FnOnce(i32) -> Result<TermSent, TerminationOutcome>,
+    send_kill: impl FnOnce(i32) -> Result<(), TerminationOutcome>,
+    mut wait: impl FnMut(i32, Duration) -> bool,
+) -> TerminationOutcome {
+    match send_term(pid) {
+        Err(outcome) => outcome,
+        Ok(TermSent::Kill) if wait(pid, KILL_TIMEOUT) => TerminationOutcome::Killed,
+        Ok(TermSent::Kill) => TerminationOutcome::StillRunning,
+        Ok(TermSent::Term) if wait(pid, grace) => TerminationOutcome::Terminated,
+        Ok(TermSent::Term) => match send_kill(pid) {
+            // Exited between the last poll and SIGKILL
+            Err(TerminationOutcome::NotFound) => TerminationOutcome::Terminated,
+            Err(outcome) => outcome,
+            Ok(()) if wait(pid, KILL_TIMEOUT) => TerminationOutcome::Killed,
+            Ok(()) => TerminationOutcome::StillRunning,
+        },
+    }
+}
+
+#[cfg(unix)]
+fn signal(pid: i32, sig: libc::c_int) -> Result<(), TerminationOutcome> {
+    // pid <= 0 would address a whole process group or every process
+    if pid <= 0 {
+        return Err(TerminationOutcome::NotFound);
+    }
+    if unsafe { libc::kill(pid, sig) } == 0 {
+        return Ok(());
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::EPERM) => Err(TerminationOutcome::PermissionDenied),
+        _ => Err(TerminationOutcome::NotFound),
+    }
+}
+
+#[cfg(unix)]
+fn send_term(pid: i32) -> Result<TermSent, TerminationOutcome> {
+    signal(pid, libc::SIGTERM).map(|()| TermSent::Term)
+}
+
+#[cfg(unix)]
+fn send_kill(pid: i32) -> Result<(), TerminationOutcome> {
+    signal(pid, libc::SIGKILL)
+}
+
+// A zombie has exited and only waits for its parent to reap it
+#[cfg(target_os = "linux")]
+fn is_zombie(pid: i32) -> bool {
+    std::fs::read_to_string(format!("/proc/{}/stat", pid))
+        .ok()
+        .and_then(|stat| stat.rfind(')').map(|i| stat[i + 1..].trim_start().starts_with('Z')))
+        .unwrap_or(false)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn is_zombie(_pid: i32) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn is_alive(pid: i32) -> bool {
+    // Signal 0 only checks existence; EPERM still means the process is there
+    let exists = unsafe { libc::kill(pid, 0) } == 0
+        || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+    exists && !is_zombie(pid)
+}
+
+// Windows has no SIGTERM equivalent for arbitrary processes, so the grace period
+// only applies where sysinfo can deliver a polite signal
+#[cfg(windows)]
+fn with_process<T>(pid: i32, f: impl FnOnce(&sysinfo::Process) -> T) -> Option<T> {
+    use sysinfo::{PidExt, SystemExt};
+    let mut sys = sysinfo::System::new();
+    let sys_pid = sysinfo::Pid::from_u32(pid as u32);
+    sys.refresh_process(sys_pid);
+    sys.process(sys_pid).map(f)
+}
+
+#[cfg(windows)]
+fn send_term(pid: i32) -> Result<TermSent, TerminationOutcome> {
+    use sysinfo::{ProcessExt, Signal};
+    match with_process(pid, |p| p.kill_with(Signal::Term)) {
+        None => Err(TerminationOutcome::NotFound),
+        Some(Some(true)) => Ok(TermSent::Term),
+        Some(Some(false)) => Err(TerminationOutcome::PermissionDenied),
+        // Not supported: go straight to a hard kill
+        Some(None) => send_kill(pid).map(|()| TermSent::Kill),
+    }
+}
+
+#[cfg(windows)]
+fn send_kill(pid: i32) -> Result<(), TerminationOutcome> {
+    use sysinfo::ProcessExt;
+    match with_process(pid, |p| p.kill()) {
+        None => Err(TerminationOutcome::NotFound),
+        Some(true) => Ok(()),
+        Some(false) => Err(TerminationOutcome::PermissionDenied),
+    }
+}
+
+#[cfg(windows)]
+fn is_alive(pid: i32) -> bool {
+    with_process(pid, |_| ()).is_some()
+}
+
+// A single process, or every member of a process group (as a shell job or a
+// build tool's worker pool usually is)
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessTarget {
+    Pid(i32),
+    Group(i32),
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlErrorKind {
+    NotFound,
+    PermissionDenied,
+    InvalidArgument,
+    Unsupported,
+    Protected, // matched a rule in the protection policy
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ControlError {
+    pub kind: ControlErrorKind,
+    pub message: String,
+}
+
+impl ControlError {
+    pub(crate) fn new(kind: ControlErrorKind, message: impl Into<String>) -> Self {
+        ControlError {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    #[cfg_attr(not(unix), allow(dead_code))]
+    fn from_errno(target: ProcessTarget, what: &str) -> Self {
+        let err = std::io::Error::last_os_error();
+        let kind = match err.raw_os_error() {
+            #[cfg(unix)]
+            Some(libc::ESRCH) => ControlErrorKind::NotFound,
+            #[cfg(unix)]
+            Some(libc::EPERM) | Some(libc::EACCES) => ControlErrorKind::PermissionDenied,
+            _ => ControlErrorKind::InvalidArgument,
+        };
+        let message = match kind {
+            ControlErrorKind::NotFound => format!("{} does not exist", target),
+            ControlErrorKind::PermissionDenied => format!(
+                "Not permitted to {} {}: it belongs to another user, or raising priority requires root",
+                what, target
+            ),
+            _ => format!("Failed to {} {}: {}", what, target, err),
+        };
+        ControlError::new(kind, message)
+    }
+
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    fn unsupported(what: &str) -> Self {
+        ControlError::new(ControlErrorKind::Unsupported, format!("Cannot {} on this platform", what))
+    }
+}
+
+impl std::fmt::Display for ProcessTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProcessTarget::Pid(pid) => write!(f, "process {}", pid),
+            ProcessTarget::Group(pgid) => write!(f, "process group {}", pgid),
+        }
+    }
+}
+
+impl ProcessTarget {
+    // pid/pgid <= 0 would address the caller's own group or every process
+    #[cfg_attr(not(unix), allow(dead_code))]
+    fn id(self) -> Result<i32, ControlError> {
+        let (ProcessTarget::Pid(id) | ProcessTarget::Group(id)) = self;
+        if id <= 0 {
+            return Err(ControlError::new(ControlErrorKind::InvalidArgument, format!("Invalid id {}", id)));
+        }
+        Ok(id)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IoPriorityClass {
+    None, // follows the CPU nice value
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SchedulingInfo {
+    pub pid: i32,
+    pub pgid: Option<i32>,
+    pub nice: Option<i32>,
+    pub io_class: Option<IoPriorityClass>,
+    pub io_level: Option<u8>, // 0 (highest) to 7, for realtime and best-effort
+    pub affinity: Option<Vec<usize>>,
+}
+
+#[cfg(unix)]
+fn send_signal(target: ProcessTarget, sig: libc::c_int, what: &str) -> Result<(), ControlError> {
+    let id = target.id()?;
+    let who = match target {
+        ProcessTarget::Pid(_) => id,
+        ProcessTarget::Group(_) => -id,
+    };
+    if unsafe { libc::kill(who, sig) } == 0 {
+        Ok(())
+    } else {
+        Err(ControlError::from_errno(target, what))
+    }
+}
+
+// SIGSTOP cannot be caught, so the target freezes until resumed
+pub fn suspend(target: ProcessTarget) -> Result<(), ControlError> {
+    #[cfg(unix)]
+    {
+        send_signal(target, libc::SIGSTOP, "suspend")
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = target;
+        Err(ControlError::unsupported("suspend processes"))
+    }
+}
+
+pub fn resume(target: ProcessTarget) -> Result<(), ControlError> {
+    #[cfg(unix)]
+    {
+        send_signal(target, libc::SIGCONT, "resume")
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = target;
+        Err(ControlError::unsupported("resume processes"))
+    }
+}
+
+// SIGTERM, or SIGKILL when `force`, without waiting for the process to exit
+pub fn signal_exit(target: ProcessTarget, force: bool) -> Result<(), ControlError> {
+    #[cfg(unix)]
+    {
+        let (sig, what) = if force { (libc::SIGKILL, "kill") } else { (libc::SIGTERM, "terminate") };
+        send_signal(target, sig, what)
+    }
+
+    #[cfg(windows)]
+    {
+        let ProcessTarget::Pid(pid) = target else {
+            return Err(ControlError::unsupported("signal process groups"));
+        };
+        let sent = if force { send_kill(pid) } else { send_term(pid).map(|_| ()) };
+        sent.map_err(|outcome| match outcome {
+            TerminationOutcome::PermissionDenied => {
+                ControlError::new(ControlErrorKind::PermissionDenied, format!("Not permitted to end {}", target))
+            }
+            _ => ControlError::new(ControlErrorKind::NotFound, format!("{} does not exist", target)),
+        })
+    }
+}
+
+// Lowering priority (higher nice) is always allowed on your own processes;
+// raising it needs CAP_SYS_NICE
+pub fn set_nice(target: ProcessTarget, nice: i32) -> Result<(), ControlError> {
+    if !(-20..=19).contains(&nice) {
+        return Err(ControlError::new(
+            ControlErrorKind::InvalidArgument,
+            format!("Nice value {} is outside -20..=19", nice),
+        ));
+    }
+
+    #[cfg(unix)]
+    {
+        let id = target.id()?;
+        let which = match target {
+            ProcessTarget::Pid(_) => libc::PRIO_PROCESS,
+            ProcessTarget::Group(_) => libc::PRIO_PGRP,
+        };
+        if unsafe { libc::setpriority(which, id as libc::id_t, nice) } == 0 {
+            Ok(())
+        } else {
+            Err(ControlError::from_errno(target, "renice"))
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = target;
+        Err(ControlError::unsupported("change process priority"))
+    }
+}
+
+// ioprio_set(2) values from include/uapi/linux/ioprio.h
+#[cfg(target_os = "linux")]
+mod ioprio {
+    pub const WHO_PROCESS: libc::c_int = 1;
+    pub const WHO_PGRP: libc::c_int = 2;
+    pub const CLASS_SHIFT: u32 = 13;
+    pub const DATA_MASK: libc::c_int = (1 << CLASS_SHIFT) - 1;
+}
+
+pub fn set_io_priority(target: ProcessTarget, class: IoPriorityClass, level: Option<u8>) -> Result<(), ControlError> {
+    let level = level.unwrap_or(4);
+    if level > 7 {
+        return Err(ControlError::new(
+            ControlErrorKind::InvalidArgument,
+            format!("I/O priority level {} is outside 0..=7", level),
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let id = target.id()?;
+        let which = match target {
+            ProcessTarget::Pid(_) => ioprio::WHO_PROCESS,
+            ProcessTarget::Group(_) => ioprio::WHO_PGRP,
+        };
+        let (class_bits, data) = match class {
+            IoPriorityClass::None => (0, 0),
+            IoPriorityClass::Realtime => (1, level as libc::c_int),
+            IoPriorityClass::BestEffort => (2, level as libc::c_int),
+            IoPriorityClass::Idle => (3, 0),
+        };
+        let prio = (class_bits << ioprio::CLASS_SHIFT) | data;
+        if unsafe { libc::syscall(libc::SYS_ioprio_set, which, id, prio) } == 0 {
+            Ok(())
+        } else {
+            Err(ControlError::from_errno(target, "change the I/O priority of"))
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (target, class);
+        Err(ControlError::unsupported("change I/O priority"))
+    }
+}
+
+// Members of a process group, from the pgrp field of /proc/<pid>/stat
+#[cfg(target_os = "linux")]
+pub(crate) fn group_members(pgid: i32) -> Vec<i32> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|e| {
+            let pid = e.file_name().to_str()?.parse::<i32>().ok()?;
+            (read_stat_field(pid, 2)? == pgid).then_some(pid)
+        })
+        .collect()
+}
+
+// Numeric field `index` of /proc/<pid>/stat, counted from the state field
+#[cfg(target_os = "linux")]
+fn read_stat_field(pid: i32, index: usize) -> Option<i32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    stat[stat.rfind(')')? + 1..].split_whitespace().nth(index)?.parse().ok()
+}
+
+// Restrict the target to the given logical CPUs. Affinity has no process-group
+// form in the kernel, so groups are applied member by member.
+pub fn set_affinity(target: ProcessTarget, cpus: &[usize]) -> Result<(), ControlError> {
+    #[cfg(target_os = "linux")]
+    {
+        let id = target.id()?;
+        let max_cpus = 8 * std::mem::size_of::<libc::cpu_set_t>();
+        if cpus.is_empty() || cpus.iter().any(|&c| c >= max_cpus) {
+            return Err(ControlError::new(
+                ControlErrorKind::InvalidArgument,
+                format!("CPU list must be non-empty and below {}", max_cpus),
+            ));
+        }
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for &cpu in cpus {
+            unsafe { libc::CPU_SET(cpu, &mut set) };
+        }
+
+        let pids = match target {
+            ProcessTarget::Pid(_) => vec![id],
+            ProcessTarget::Group(_) => group_members(id),
+        };
+        if pids.is_empty() {
+            return Err(ControlError::new(ControlErrorKind::NotFound, format!("{} does not exist", target)));
+        }
+        for pid in pids {
+            if unsafe { libc::sched_setaffinity(pid, std::mem::size_of::<libc::cpu_set_t>(), &set) } != 0 {
+                return Err(ControlError::from_errno(ProcessTarget::Pid(pid), "set the CPU affinity of"));
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (target, cpus);
+        Err(ControlError::unsupported("set CPU affinity"))
+    }
+}
+
+pub fn scheduling_info(pid: i32) -> Result<SchedulingInfo, ControlError> {
+    #[cfg(target_os = "linux")]
+    {
+        let target = ProcessTarget::Pid(pid);
+        target.id()?;
+        // Fields after the state: ppid(1) pgrp(2) ... nice(16)
+        let Some(pgid) = read_stat_field(pid, 2) else {
+            return Err(ControlError::new(ControlErrorKind::NotFound, format!("{} does not exist", target)));
+        };
+
+        let prio = unsafe { libc::syscall(libc::SYS_ioprio_get, ioprio::WHO_PROCESS, pid) };
+        let (io_class, io_level) = if prio < 0 {
+            (None, None)
+        } else {
+            let prio = prio as libc::c_int;
+            let data = (prio & ioprio::DATA_MASK) as u8;
+            match prio >> ioprio::CLASS_SHIFT {
+                1 => (Some(IoPriorityClass::Realtime), Some(data)),
+                2 => (Some(IoPriorityClass::BestEffort), Some(data)),
+                3 => (Some(IoPriorityClass::Idle), None),
+                _ => (Some(IoPriorityClass::None), None),
+            }
+        };
+
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        let affinity = (unsafe { libc::sched_getaffinity(pid, std::mem::size_of::<libc::cpu_set_t>(), &mut set) } == 0)
+            .then(|| {
+                (0..8 * std::mem::size_of::<libc::cpu_set_t>())
+                    .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+                    .collect()
+            });
+
+        Ok(SchedulingInfo {
+            pid,
+            pgid: Some(pgid),
+            nice: read_stat_field(pid, 16),
+            io_class,
+            io_level,
+            affinity,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        Err(ControlError::unsupported("read scheduling settings"))
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    fn spawn(script: &str) -> std::process::Child {
+        let child = Command::new("sh").arg("-c").arg(script).stdout(Stdio::null()).spawn().unwrap();
+        // Give the shell time to install its traps
+        thread::sleep(Duration::from_millis(100));
+        child
+    }
+
+    #[test]
+    fn sigterm_is_enough_for_a_cooperative_process() {
+        let mut child = spawn("exec sleep 30");
+        let result = terminate(child.id() as i32, Duration::from_secs(2));
+        assert_eq!(result.outcome, TerminationOutcome::Terminated);
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn escalates_to_sigkill_after_grace_period() {
+        let mut child = spawn("trap '' TERM; while :; do sleep 0.05; done");
+        let result = terminate(child.id() as i32, Duration::from_millis(300));
+        assert_eq!(result.outcome, TerminationOutcome::Killed);
+        assert!(result.elapsed_ms >= 300);
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn reports_missing_and_foreign_processes() {
+        // Above any possible pid_max
+        assert_eq!(terminate(i32::MAX, Duration::ZERO).outcome, TerminationOutcome::NotFound);
+        assert_eq!(terminate(0, Duration::ZERO).outcome, TerminationOutcome::NotFound);
+        if unsafe { libc::geteuid() } != 0 {
+            assert_eq!(terminate(1, Duration::ZERO).outcome, TerminationOutcome::PermissionDenied);
+        }
+    }
+
+    #[test]
+    fn suspends_renices_and_pins_a_process() {
+        let mut child = spawn("exec sleep 30");
+        let pid = child.id() as i32;
+        let target = ProcessTarget::Pid(pid);
+
+        suspend(target).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        let state = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+        assert!(state[state.rfind(')').unwrap() + 1..].trim_start().starts_with('T'));
+        resume(target).unwrap();
+
+        set_nice(target, 15).unwrap();
+        set_io_priority(target, IoPriorityClass::BestEffort, Some(6)).unwrap();
+        set_affinity(target, &[0]).unwrap();
+        let info = scheduling_info(pid).unwrap();
+        assert_eq!(info.nice, Some(15));
+        assert_eq!(info.io_class, Some(IoPriorityClass::BestEffort));
+        assert_eq!(info.io_level, Some(6));
+        assert_eq!(info.affinity, Some(vec![0]));
+
+        terminate(pid, Duration::from_secs(1));
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        let err = set_nice(ProcessTarget::Pid(1), 40).unwrap_err();
+        assert_eq!(err.kind, ControlErrorKind::InvalidArgument);
+        let err = suspend(ProcessTarget::Group(0)).unwrap_err();
+        assert_eq!(err.kind, ControlErrorKind::InvalidArgument);
+        let err = resume(ProcessTarget::Pid(i32::MAX)).unwrap_err();
+        assert_eq!(err.kind, ControlErrorKind::NotFound);
+    }
+
+    const GRACE: Duration = Duration::from_secs(3);
+
+    // `exits` answers each wait in turn: did the process exit in time?
+    fn outcome(
+        term: Result<TermSent, TerminationOutcome>,
+        kill: Result<(), TerminationOutcome>,
+        exits: &[bool],
+    ) -> TerminationOutcome {
+        let mut exits = exits.iter().copied();
+        escalate(42, GRACE, |_| term, |_| kill, |_, _| exits.next().unwrap())
+    }
+
+    #[test]
+    fn reports_how_the_process_ended() {
+        assert_eq!(outcome(Ok(TermSent::Term), Ok(()), &[true]), TerminationOutcome::Terminated);
+        assert_eq!(outcome(Ok(TermSent::Term), Ok(()), &[false, true]), TerminationOutcome::Killed);
+        assert_eq!(outcome(Ok(TermSent::Term), Ok(()), &[false, false]), TerminationOutcome::StillRunning);
+        assert_eq!(
+            outcome(Ok(TermSent::Term), Err(TerminationOutcome::NotFound), &[false]),
+            TerminationOutcome::Terminated
+        );
+        assert_eq!(
+            outcome(Err(TerminationOutcome::PermissionDenied), Ok(()), &[]),
+            TerminationOutcome::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn a_kill_in_place_of_term_is_reported_as_killed() {
+        assert_eq!(outcome(Ok(TermSent::Kill), Ok(()), &[true]), TerminationOutcome::Killed);
+        assert_eq!(outcome(Ok(TermSent::Kill), Ok(()), &[false]), TerminationOutcome::StillRunning);
+    }
+}
@@ -0,0 +1,395 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::proctrack::clock_ticks_per_second;
+
+// How long threads are observed to derive their CPU usage
+const THREAD_SAMPLE_WINDOW: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Debug)]
+pub struct ProcessDetails {
+    pub pid: u32,
+    pub name: String,
+    pub cmdline: Vec<String>,
+    // None when the process belongs to another user
+    pub environment: Option<BTreeMap<String, String>>,
+    pub cwd: Option<String>,
+    pub exe: Option<String>,
+    pub fds: Option<Vec<FdInfo>>,
+    pub maps: Option<MapsSummary>,
+    pub threads: Vec<ThreadInfo>,
+    pub cgroups: Vec<CgroupEntry>,
+    pub namespaces: Vec<NamespaceInfo>,
+    pub limits: Vec<ResourceLimit>,
+    pub io: Option<IoCounters>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FdInfo {
+    pub fd: u32,
+    pub target: String,
+    pub kind: String, // "file", "socket", "pipe", "anon_inode", "device" or "other"
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct MapsSummary {
+    pub regions: usize,
+    pub total_kb: u64, // virtual size of every mapping
+    pub file_backed_kb: u64,
+    pub anonymous_kb: u64,
+    pub heap_kb: u64,
+    pub stack_kb: u64,
+    pub mapped_files: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ThreadInfo {
+    pub tid: u32,
+    pub name: String,
+    pub state: String, // single-letter code from proc(5), e.g. "R", "S", "D"
+    pub cpu: f32,      // percent of one core over the sample window
+    pub cpu_time_secs: f64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CgroupEntry {
+    pub hierarchy: u32,
+    pub controllers: Vec<String>, // empty for the unified (v2) hierarchy
+    pub path: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NamespaceInfo {
+    pub kind: String, // "net", "pid", "mnt", ...
+    pub inode: u64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ResourceLimit {
+    pub name: String,
+    pub soft: Option<u64>, // None means unlimited
+    pub hard: Option<u64>,
+    pub units: Option<String>,
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct IoCounters {
+    pub rchar: u64,
+    pub wchar: u64,
+    pub syscr: u64,
+    pub syscw: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub cancelled_write_bytes: u64,
+}
+
+fn read_link_string(path: &Path) -> Option<String> {
+    fs::read_link(path).ok().map(|p| p.to_string_lossy().into_owned())
+}
+
+// NUL-separated, as in /proc/<pid>/cmdline and /proc/<pid>/environ
+fn split_nul(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .split(|&b| b == 0)
+        .filter(|s| !s.is_empty())
+        .map(|s| String::from_utf8_lossy(s).into_owned())
+        .collect()
+}
+
+fn fd_kind(target: &str) -> &'static str {
+    if target.starts_with("socket:[") {
+        "socket"
+    } else if target.starts_with("pipe:[") {
+        "pipe"
+    } else if target.starts_with("anon_inode:") {
+        "anon_inode"
+    } else if target.starts_with("/dev/") {
+        "device"
+    } else if target.starts_with('/') {
+        "file"
+    } else {
+        "other"
+    }
+}
+
+pub fn parse_maps(text: &str) -> MapsSummary {
+    let mut summary = MapsSummary::default();
+    for line in text.lines() {
+        let mut fields = line.splitn(6, char::is_whitespace);
+        let Some((start, end)) = fields.next().and_then(|range| range.split_once('-')) else {
+            continue;
+        };
+        let (Ok(start), Ok(end)) = (u64::from_str_radix(start, 16), u64::from_str_radix(end, 16)) else {
+            continue;
+        };
+        let kb = end.saturating_sub(start) / 1024;
+        let path = fields.nth(4).map(str::trim).unwrap_or("");
+
+        summary.regions += 1;
+        summary.total_kb += kb;
+        match path {
+            "" => summary.anonymous_kb += kb,
+            "[heap]" => summary.heap_kb += kb,
+            p if p.starts_with("[stack") => summary.stack_kb += kb,
+            p if p.starts_with('/') => {
+                summary.file_backed_kb += kb;
+                summary.mapped_files.push(p.trim_end_matches(" (deleted)").to_string());
+            }
+            _ => {} // [vdso], [vvar], [anon:...] and friends
+        }
+    }
+    summary.mapped_files.sort();
+    summary.mapped_files.dedup();
+    summary
+}
+
+// "0::/user.slice/..." on cgroup v2, "4:memory:/docker/<id>" on v1
+pub fn parse_cgroup(text: &str) -> Vec<CgroupEntry> {
+    text.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ':');
+            let hierarchy = parts.next()?.parse().ok()?;
+            let controllers = parts.next()?;
+            Some(CgroupEntry {
+                hierarchy,
+                controllers: controllers.split(',').filter(|c| !c.is_empty()).map(str::to_string).collect(),
+                path: parts.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+// /proc/<pid>/limits is a fixed-width table; the column offsets come from the header
+pub fn parse_limits(text: &str) -> Vec<ResourceLimit> {
+    let mut lines = text.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let (Some(soft_at), Some(hard_at), Some(units_at)) =
+        (header.find("Soft Limit"), header.find("Hard Limit"), header.find("Units"))
+    else {
+        return Vec::new();
+    };
+    let column = |line: &str, from: usize, to: usize| -> String {
+        line.get(from..to.min(line.len())).unwrap_or("").trim().to_string()
+    };
+    let value = |v: String| if v == "unlimited" { None } else { v.parse().ok() };
+
+    lines
+        .filter(|line| line.len() > soft_at)
+        .map(|line| {
+            let units = column(line, units_at, line.len());
+            ResourceLimit {
+                name: column(line, 0, soft_at),
+                soft: value(column(line, soft_at, hard_at)),
+                hard: value(column(line, hard_at, units_at)),
+                units: (!units.is_empty()).then_some(units),
+            }
+        })
+        .collect()
+}
+
+pub fn parse_io(text: &str) -> IoCounters {
+    let mut io = IoCounters::default();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().parse().unwrap_or(0);
+        match key {
+            "rchar" => io.rchar = value,
+            "wchar" => io.wchar = value,
+            "syscr" => io.syscr = value,
+            "syscw" => io.syscw = value,
+            "read_bytes" => io.read_bytes = value,
+            "write_bytes" => io.write_bytes = value,
+            "cancelled_write_bytes" => io.cancelled_write_bytes = value,
+            _ => {}
+        }
+    }
+    io
+}
+
+// (comm, state, utime + stime) from a /proc/<pid>/task/<tid>/stat line
+pub fn parse_task_stat(stat: &str) -> Option<(String, String, u64)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = stat[close + 1..].split_whitespace().collect();
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+    Some((name, fields.first()?.to_string(), utime + stime))
+}
+
+fn read_task_ticks(task_dir: &Path) -> BTreeMap<u32, (String, String, u64)> {
+    let Ok(entries) = fs::read_dir(task_dir) else {
+        return BTreeMap::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|e| {
+            let tid = e.file_name().to_str()?.parse::<u32>().ok()?;
+            let stat = fs::read_to_string(e.path().join("stat")).ok()?;
+            Some((tid, parse_task_stat(&stat)?))
+        })
+        .collect()
+}
+
+// Blocks for THREAD_SAMPLE_WINDOW to measure per-thread CPU
+fn read_threads(task_dir: &Path) -> Vec<ThreadInfo> {
+    let before = read_task_ticks(task_dir);
+    let started = Instant::now();
+    thread::sleep(THREAD_SAMPLE_WINDOW);
+    let after = read_task_ticks(task_dir);
+    let elapsed = started.elapsed().as_secs_f64();
+    let hz = clock_ticks_per_second() as f64;
+
+    after
+        .into_iter()
+        .map(|(tid, (name, state, ticks))| {
+            // Threads spawned during the window count from zero
+            let delta = ticks.saturating_sub(before.get(&tid).map_or(0, |b| b.2));
+            ThreadInfo {
+                tid,
+                name,
+                state,
+                cpu: (delta as f64 / hz / elapsed * 100.0) as f32,
+                cpu_time_secs: ticks as f64 / hz,
+            }
+        })
+        .collect()
+}
+
+fn read_fds(fd_dir: &Path) -> Option<Vec<FdInfo>> {
+    let mut fds: Vec<FdInfo> = fs::read_dir(fd_dir)
+        .ok()?
+        .flatten()
+        .filter_map(|e| {
+            let fd = e.file_name().to_str()?.parse::<u32>().ok()?;
+            let target = read_link_string(&e.path())?;
+            Some(FdInfo {
+                fd,
+                kind: fd_kind(&target).to_string(),
+                target,
+            })
+        })
+        .collect();
+    fds.sort_by_key(|f| f.fd);
+    Some(fds)
+}
+
+fn read_namespaces(ns_dir: &Path) -> Vec<NamespaceInfo> {
+    let Ok(entries) = fs::read_dir(ns_dir) else {
+        return Vec::new();
+    };
+    let mut namespaces: Vec<NamespaceInfo> = entries
+        .flatten()
+        .filter_map(|e| {
+            // "net:[4026531840]"
+            let target = read_link_string(&e.path())?;
+            let (kind, rest) = target.split_once(":[")?;
+            Some(NamespaceInfo {
+                kind: kind.to_string(),
+                inode: rest.trim_end_matches(']').parse().ok()?,
+            })
+        })
+        .collect();
+    namespaces.sort_by(|a, b| a.kind.cmp(&b.kind));
+    namespaces
+}
+
+// Everything /proc exposes about one process. Files that need ptrace access
+// (environ, fd, io, maps of other users' processes) are left empty when denied.
+pub fn read_details(proc_root: &Path, pid: u32) -> Result<ProcessDetails, String> {
+    let dir = proc_root.join(pid.to_string());
+    let name = fs::read_to_string(dir.join("comm"))
+        .map(|c| c.trim().to_string())
+        .map_err(|_| format!("Process {} not found", pid))?;
+
+    Ok(ProcessDetails {
+        pid,
+        name,
+        cmdline: fs::read(dir.join("cmdline")).map(|b| split_nul(&b)).unwrap_or_default(),
+        environment: fs::read(dir.join("environ")).ok().map(|b| {
+            split_nul(&b)
+                .into_iter()
+                .filter_map(|kv| kv.split_once('=').map(|(k, v)| (k.to_string(), v.to_string())))
+                .collect()
+        }),
+        cwd: read_link_string(&dir.join("cwd")),
+        exe: read_link_string(&dir.join("exe")),
+        fds: read_fds(&dir.join("fd")),
+        maps: fs::read_to_string(dir.join("maps")).ok().map(|m| parse_maps(&m)),
+        threads: read_threads(&dir.join("task")),
+        cgroups: fs::read_to_string(dir.join("cgroup")).map(|c| parse_cgroup(&c)).unwrap_or_default(),
+        namespaces: read_namespaces(&dir.join("ns")),
+        limits: fs::read_to_string(dir.join("limits")).map(|l| parse_limits(&l)).unwrap_or_default(),
+        io: fs::read_to_string(dir.join("io")).ok().map(|i| parse_io(&i)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proc_pid").join(name)).unwrap()
+    }
+
+    #[test]
+    fn summarizes_memory_maps() {
+        let maps = parse_maps(&fixture("maps"));
+        assert_eq!(maps.regions, 11);
+        assert_eq!(maps.total_kb, 9704);
+        assert_eq!(maps.heap_kb, 3204);
+        assert_eq!(maps.stack_kb, 132);
+        assert_eq!(maps.anonymous_kb, 4096);
+        assert_eq!(maps.file_backed_kb, 2248);
+        assert_eq!(
+            maps.mapped_files,
+            vec!["/memfd:mozilla-ipc", "/usr/lib/firefox/firefox", "/usr/lib/x86_64-linux-gnu/libc.so.6"]
+        );
+    }
+
+    #[test]
+    fn parses_limits_table() {
+        let limits = parse_limits(&fixture("limits"));
+        assert_eq!(limits.len(), 16);
+        let stack = limits.iter().find(|l| l.name == "Max stack size").unwrap();
+        assert_eq!(stack.soft, Some(8388608));
+        assert_eq!(stack.hard, None);
+        assert_eq!(stack.units.as_deref(), Some("bytes"));
+        let nice = limits.iter().find(|l| l.name == "Max nice priority").unwrap();
+        assert_eq!(nice.soft, Some(0));
+        assert_eq!(nice.units, None);
+    }
+
+    #[test]
+    fn parses_io_and_cgroup() {
+        let io = parse_io(&fixture("io"));
+        assert_eq!(io.rchar, 48213377);
+        assert_eq!(io.read_bytes, 15622144);
+        assert_eq!(io.cancelled_write_bytes, 12288);
+
+        let cgroups = parse_cgroup(&fixture("cgroup"));
+        assert_eq!(cgroups.len(), 1);
+        assert_eq!(cgroups[0].hierarchy, 0);
+        assert!(cgroups[0].controllers.is_empty());
+        assert!(cgroups[0].path.ends_with("app-org.mozilla.firefox-1234.scope"));
+
+        let v1 = parse_cgroup("4:memory:/docker/abc\n3:cpu,cpuacct:/\n");
+        assert_eq!(v1[1].controllers, vec!["cpu", "cpuacct"]);
+    }
+
+    #[test]
+    fn parses_thread_stat_with_spaces_in_name() {
+        let (name, state, ticks) = parse_task_stat(&fixture("task_stat")).unwrap();
+        assert_eq!(name, "Web Content");
+        assert_eq!(state, "S");
+        assert_eq!(ticks, 1830);
+    }
+}
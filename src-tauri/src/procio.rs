@@ -0,0 +1,219 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Instant;
+use sysinfo::{Pid, PidExt, ProcessExt, System, SystemExt};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProcessIoRate {
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+}
+
+// Cumulative bytes a process has caused to be read from / written to storage
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IoSample {
+    pub start_time: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProcessIoUsage {
+    pub pid: i32,
+    pub name: String,
+    pub exe: Option<String>,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+}
+
+// What can be taken from the System while the sampler is locked: start times,
+// plus sysinfo's counters on platforms without /proc. On Linux the counters are
+// filled in afterwards by ProcessIoTracker::read.
+pub fn snapshot(sys: &System) -> Vec<(Pid, IoSample)> {
+    sys.processes()
+        .iter()
+        .map(|(pid, proc)| {
+            #[cfg(target_os = "linux")]
+            let (read_bytes, write_bytes) = (0, 0);
+            #[cfg(not(target_os = "linux"))]
+            let (read_bytes, write_bytes) = {
+                let usage = proc.disk_usage();
+                (usage.total_read_bytes, usage.total_written_bytes)
+            };
+            let sample = IoSample {
+                start_time: proc.start_time(),
+                read_bytes,
+                write_bytes,
+            };
+            (*pid, sample)
+        })
+        .collect()
+}
+
+pub struct ProcessIoTracker {
+    prev: HashMap<Pid, IoSample>,
+    prev_at: Option<Instant>,
+    rates: HashMap<Pid, ProcessIoRate>,
+}
+
+impl ProcessIoTracker {
+    pub fn new() -> Self {
+        ProcessIoTracker {
+            prev: HashMap::new(),
+            prev_at: None,
+            rates: HashMap::new(),
+        }
+    }
+
+    // /proc/<pid>/io counts block-device traffic rather than read()/write() calls,
+    // so page-cache hits do not show up. Other users' processes need ptrace access;
+    // sysinfo reads the same file on Linux, so those get no sample rather than zeros.
+    #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
+    pub fn read(mut samples: Vec<(Pid, IoSample)>) -> Vec<(Pid, IoSample)> {
+        #[cfg(target_os = "linux")]
+        samples.retain_mut(|(pid, sample)| {
+            let Ok(text) = std::fs::read_to_string(format!("/proc/{}/io", pid.as_u32())) else {
+                return false;
+            };
+            let io = crate::procdetails::parse_io(&text);
+            sample.read_bytes = io.read_bytes;
+            sample.write_bytes = io.write_bytes;
+            true
+        });
+        samples
+    }
+
+    pub fn apply(&mut self, samples: Vec<(Pid, IoSample)>, now: Instant) {
+        self.update_from(samples, now);
+    }
+
+    fn update_from(&mut self, samples: Vec<(Pid, IoSample)>, now: Instant) {
+        let secs = self.prev_at.map(|t| now.duration_since(t).as_secs_f64()).unwrap_or(0.0);
+        self.rates.clear();
+        if secs > 0.0 {
+            for (pid, cur) in &samples {
+                // A new start time means the pid was reused; wait for a second sample
+                let Some(prev) = self.prev.get(pid).filter(|p| p.start_time == cur.start_time) else {
+                    continue;
+                };
+                let rate = ProcessIoRate {
+                    read_bytes_per_sec: cur.read_bytes.saturating_sub(prev.read_bytes) as f64 / secs,
+                    write_bytes_per_sec: cur.write_bytes.saturating_sub(prev.write_bytes) as f64 / secs,
+                };
+                self.rates.insert(*pid, rate);
+            }
+        }
+        self.prev = samples.into_iter().collect();
+        self.prev_at = Some(now);
+    }
+
+    // None until the process has been seen in two consecutive refreshes, and
+    // for processes whose counters cannot be read
+    pub fn rate_for(&self, pid: Pid) -> Option<ProcessIoRate> {
+        self.rates.get(&pid).copied()
+    }
+
+    // Busiest processes by combined read + write rate; idle ones are left out
+    pub fn top(&self, sys: &System, limit: usize) -> Vec<ProcessIoUsage> {
+        let mut busy: Vec<(&Pid, &ProcessIoRate)> = self
+            .rates
+            .iter()
+            .filter(|(_, r)| r.read_bytes_per_sec + r.write_bytes_per_sec > 0.0)
+            .collect();
+        busy.sort_by(|a, b| {
+            let total = |r: &ProcessIoRate| r.read_bytes_per_sec + r.write_bytes_per_sec;
+            total(b.1).total_cmp(&total(a.1))
+        });
+        busy.into_iter()
+            .filter_map(|(pid, rate)| {
+                let proc = sys.process(*pid)?;
+                Some(ProcessIoUsage {
+                    pid: pid.as_u32() as i32,
+                    name: proc.name().to_string(),
+                    exe: proc.exe().to_str().map(|s| s.to_string()).filter(|s| !s.is_empty()),
+                    read_bytes_per_sec: rate.read_bytes_per_sec,
+                    write_bytes_per_sec: rate.write_bytes_per_sec,
+                })
+            })
+            .take(limit)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn sample(start_time: u64, read_bytes: u64, write_bytes: u64) -> IoSample {
+        IoSample {
+            start_time,
+            read_bytes,
+            write_bytes,
+        }
+    }
+
+    #[test]
+    fn rates_come_from_counter_deltas() {
+        let mut tracker = ProcessIoTracker::new();
+        let t0 = Instant::now();
+        let (a, b) = (Pid::from_u32(100), Pid::from_u32(200));
+
+        tracker.update_from(vec![(a, sample(5, 1000, 0)), (b, sample(7, 0, 0))], t0);
+        assert_eq!(tracker.rate_for(a), None);
+
+        tracker.update_from(
+            vec![(a, sample(5, 5000, 2000)), (b, sample(7, 0, 0))],
+            t0 + Duration::from_secs(2),
+        );
+        let rate = tracker.rate_for(a).unwrap();
+        assert_eq!(rate.read_bytes_per_sec, 2000.0);
+        assert_eq!(rate.write_bytes_per_sec, 1000.0);
+        assert_eq!(tracker.rate_for(b), Some(ProcessIoRate::default()));
+    }
+
+    #[test]
+    fn reused_pids_start_over() {
+        let mut tracker = ProcessIoTracker::new();
+        let t0 = Instant::now();
+        let pid = Pid::from_u32(300);
+
+        tracker.update_from(vec![(pid, sample(5, 1 << 30, 0))], t0);
+        // Same pid, different process with much smaller counters
+        tracker.update_from(vec![(pid, sample(9, 4096, 0))], t0 + Duration::from_secs(1));
+        assert_eq!(tracker.rate_for(pid), None);
+        tracker.update_from(vec![(pid, sample(9, 8192, 0))], t0 + Duration::from_secs(2));
+        assert_eq!(tracker.rate_for(pid).unwrap().read_bytes_per_sec, 4096.0);
+    }
+
+    #[test]
+    fn unreadable_counters_yield_no_rate() {
+        let mut tracker = ProcessIoTracker::new();
+        let t0 = Instant::now();
+        let (own, foreign) = (Pid::from_u32(100), Pid::from_u32(200));
+
+        tracker.update_from(vec![(own, sample(5, 0, 0)), (foreign, sample(7, 0, 0))], t0);
+        // The foreign process's counters became unreadable (no sample)
+        tracker.update_from(vec![(own, sample(5, 0, 0))], t0 + Duration::from_secs(1));
+        assert_eq!(tracker.rate_for(own), Some(ProcessIoRate::default()));
+        assert_eq!(tracker.rate_for(foreign), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn skips_processes_without_a_readable_io_file() {
+        let mut sys = System::new();
+        sys.refresh_processes();
+        let mut tracker = ProcessIoTracker::new();
+        tracker.apply(ProcessIoTracker::read(snapshot(&sys)), Instant::now());
+        tracker.apply(ProcessIoTracker::read(snapshot(&sys)), Instant::now());
+        for pid in sys.processes().keys() {
+            let readable = std::fs::read_to_string(format!("/proc/{}/io", pid.as_u32())).is_ok();
+            if !readable {
+                assert_eq!(tracker.rate_for(*pid), None);
+            }
+        }
+        let own = Pid::from_u32(std::process::id());
+        assert!(tracker.rate_for(own).is_some());
+    }
+}
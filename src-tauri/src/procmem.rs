@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use sysinfo::Pid;
+
+// Per-process memory in bytes. RSS counts every resident page, so summing it
+// over a multi-process app counts shared libraries and shared memory once per
+// process; PSS splits each shared page between the processes mapping it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryBreakdown {
+    pub rss: u64,
+    pub pss: Option<u64>,    // proportional share of shared pages
+    pub uss: Option<u64>,    // private pages: what exiting would free
+    pub shared: Option<u64>, // resident pages also mapped elsewhere
+    pub swap: Option<u64>,
+}
+
+// "Pss:  298133 kB" lines -> bytes by field name
+fn kb_fields(text: &str) -> impl Iterator<Item = (&str, u64)> {
+    text.lines().filter_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        let kb: u64 = value.trim().strip_suffix("kB")?.trim().parse().ok()?;
+        Some((key, kb * 1024))
+    })
+}
+
+// /proc/<pid>/smaps_rollup (Linux 4.14+), the sum of every mapping in smaps
+pub fn parse_smaps_rollup(text: &str) -> Option<MemoryBreakdown> {
+    let (mut rss, mut pss, mut shared, mut private, mut swap) = (None, None, 0, 0, None);
+    for (key, bytes) in kb_fields(text) {
+        match key {
+            "Rss" => rss = Some(bytes),
+            "Pss" => pss = Some(bytes),
+            "Shared_Clean" | "Shared_Dirty" | "Shared_Hugetlb" => shared += bytes,
+            "Private_Clean" | "Private_Dirty" | "Private_Hugetlb" => private += bytes,
+            "Swap" => swap = Some(bytes),
+            _ => {}
+        }
+    }
+    Some(MemoryBreakdown {
+        rss: rss?,
+        pss,
+        uss: Some(private),
+        shared: Some(shared),
+        swap,
+    })
+}
+
+// /proc/<pid>/status is readable for every process, unlike smaps_rollup which
+// needs ptrace access. It has no PSS/USS, and file-backed plus shmem pages
+// stand in for shared ones.
+pub fn parse_status(text: &str) -> Option<MemoryBreakdown> {
+    let (mut rss, mut file, mut shmem, mut swap) = (None, None, None, None);
+    for (key, bytes) in kb_fields(text) {
+        match key {
+            "VmRSS" => rss = Some(bytes),
+            "RssFile" => file = Some(bytes),
+            "RssShmem" => shmem = Some(bytes),
+            "VmSwap" => swap = Some(bytes),
+            _ => {}
+        }
+    }
+    Some(MemoryBreakdown {
+        rss: rss?,
+        pss: None,
+        uss: None,
+        shared: file.zip(shmem).map(|(f, s)| f + s),
+        swap,
+    })
+}
+
+// Kernel threads have neither file populated and yield None
+pub fn read_breakdown(proc_root: &Path, pid: i32) -> Option<MemoryBreakdown> {
+    let dir = proc_root.join(pid.to_string());
+    fs::read_to_string(dir.join("smaps_rollup"))
+        .ok()
+        .and_then(|text| parse_smaps_rollup(&text))
+        .or_else(|| fs::read_to_string(dir.join("status")).ok().and_then(|text| parse_status(&text)))
+}
+
+// smaps_rollup makes the kernel walk every mapping of the process, so the
+// sampler reads it once per process refresh and commands serve the result
+pub struct ProcessMemoryTracker {
+    breakdowns: HashMap<Pid, MemoryBreakdown>,
+}
+
+impl ProcessMemoryTracker {
+    pub fn new() -> Self {
+        ProcessMemoryTracker {
+            breakdowns: HashMap::new(),
+        }
+    }
+
+    // Called with the sampler lock released
+    pub fn read(pids: &[Pid]) -> HashMap<Pid, MemoryBreakdown> {
+        #[cfg(target_os = "linux")]
+        {
+            use sysinfo::PidExt;
+            let proc_root = Path::new(crate::connections::PROC_ROOT);
+            pids.iter()
+                .filter_map(|pid| Some((*pid, read_breakdown(proc_root, pid.as_u32() as i32)?)))
+                .collect()
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = pids;
+            HashMap::new()
+        }
+    }
+
+    pub fn apply(&mut self, breakdowns: HashMap<Pid, MemoryBreakdown>) {
+        self.breakdowns = breakdowns;
+    }
+
+    pub fn breakdown_for(&self, pid: Pid) -> Option<MemoryBreakdown> {
+        self.breakdowns.get(&pid).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proc_pid").join(name)).unwrap()
+    }
+
+    #[test]
+    fn parses_smaps_rollup() {
+        let mem = parse_smaps_rollup(&fixture("smaps_rollup")).unwrap();
+        assert_eq!(mem.rss, 412_844 * 1024);
+        assert_eq!(mem.pss, Some(298_133 * 1024));
+        assert_eq!(mem.uss, Some((37_296 + 249_224) * 1024));
+        assert_eq!(mem.shared, Some((118_220 + 8_104) * 1024));
+        assert_eq!(mem.swap, Some(20_480 * 1024));
+        // Every resident page is either shared or private
+        assert_eq!(mem.shared.unwrap() + mem.uss.unwrap(), mem.rss);
+    }
+
+    #[test]
+    fn falls_back_to_status() {
+        let mem = parse_status(&fixture("status")).unwrap();
+        assert_eq!(mem.rss, 412_844 * 1024);
+        assert_eq!(mem.pss, None);
+        assert_eq!(mem.shared, Some((163_700 + 1_464) * 1024));
+        assert_eq!(mem.swap, Some(20_480 * 1024));
+
+        // Kernel threads: no Vm* lines at all
+        assert_eq!(parse_status("Name:\tkthreadd\nState:\tS (sleeping)\n"), None);
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::time::Instant;
+use sysinfo::{Pid, PidExt};
+
+// Cumulative payload bytes of one TCP socket, from its tcp_info
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SocketCounters {
+    pub inode: u64,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+// Every TCP socket's counters and the socket inode -> pid owners
+pub type SocketSample = (Vec<SocketCounters>, HashMap<u64, u32>);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProcessNetRate {
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
+}
+
+// Bytes moved per process since the previous sample. Sockets that did not exist
+// then were opened during the interval, so everything they carried counts.
+fn attribute(
+    prev: &HashMap<u64, SocketCounters>,
+    cur: &[SocketCounters],
+    owners: &HashMap<u64, u32>,
+) -> HashMap<u32, (u64, u64)> {
+    let mut per_pid: HashMap<u32, (u64, u64)> = HashMap::new();
+    for socket in cur {
+        let Some(&pid) = owners.get(&socket.inode) else {
+            continue;
+        };
+        let (rx, tx) = match prev.get(&socket.inode) {
+            Some(p) => (
+                socket.rx_bytes.saturating_sub(p.rx_bytes),
+                socket.tx_bytes.saturating_sub(p.tx_bytes),
+            ),
+            None => (socket.rx_bytes, socket.tx_bytes),
+        };
+        let total = per_pid.entry(pid).or_default();
+        total.0 += rx;
+        total.1 += tx;
+    }
+    per_pid
+}
+
+// Per-process TCP throughput, attributed through socket ownership. UDP sockets
+// carry no byte counters and sockets in other network namespaces are not visible,
+// so this undercounts for those.
+pub struct ProcessNetTracker {
+    prev: HashMap<u64, SocketCounters>,
+    prev_at: Option<Instant>,
+    rates: HashMap<u32, ProcessNetRate>,
+    available: bool,
+}
+
+impl ProcessNetTracker {
+    pub fn new() -> Self {
+        ProcessNetTracker {
+            prev: HashMap::new(),
+            prev_at: None,
+            rates: HashMap::new(),
+            available: false,
+        }
+    }
+
+    // Walks every fd table, so the sampler calls this with its lock released.
+    // None when per-socket counters cannot be read on this system.
+    pub fn read() -> Option<SocketSample> {
+        #[cfg(target_os = "linux")]
+        {
+            let sockets = sock_diag::tcp_socket_counters().ok()?;
+            let owners = crate::connections::socket_owners(std::path::Path::new(crate::connections::PROC_ROOT));
+            Some((sockets, owners))
+        }
+        #[cfg(not(target_os = "linux"))]
+        None
+    }
+
+    pub fn apply(&mut self, sample: Option<SocketSample>, now: Instant) {
+        match sample {
+            Some((sockets, owners)) => self.update_from(&sockets, &owners, now),
+            None => self.available = false,
+        }
+    }
+
+    fn update_from(&mut self, sockets: &[SocketCounters], owners: &HashMap<u64, u32>, now: Instant) {
+        let sockets: Vec<SocketCounters> = sockets.iter().copied().filter(|s| s.inode != 0).collect();
+        self.rates = match self.prev_at.map(|t| now.duration_since(t).as_secs_f64()) {
+            Some(secs) if secs > 0.0 => attribute(&self.prev, &sockets, owners)
+                .into_iter()
+                .map(|(pid, (rx, tx))| {
+                    let rate = ProcessNetRate {
+                        rx_bytes_per_sec: rx as f64 / secs,
+                        tx_bytes_per_sec: tx as f64 / secs,
+                    };
+                    (pid, rate)
+                })
+                .collect(),
+            _ => HashMap::new(),
+        };
+        self.prev = sockets.into_iter().map(|s| (s.inode, s)).collect();
+        self.prev_at = Some(now);
+        self.available = true;
+    }
+
+    // None when per-socket counters cannot be read on this system
+    pub fn rate_for(&self, pid: Pid) -> Option<ProcessNetRate> {
+        if !self.available {
+            return None;
+        }
+        Some(self.rates.get(&pid.as_u32()).copied().unwrap_or_default())
+    }
+}
+
+// Minimal NETLINK_SOCK_DIAG client: dumps every TCP socket with its tcp_info
+#[cfg(target_os = "linux")]
+mod sock_diag {
+    use super::SocketCounters;
+    use std::io;
+
+    const SOCK_DIAG_BY_FAMILY: u16 = 20;
+    const INET_DIAG_INFO: u16 = 2;
+    const NLMSG_HDR_LEN: usize = 16;
+    const INET_DIAG_REQ_LEN: usize = 56;
+    const INET_DIAG_MSG_LEN: usize = 72;
+    // Offsets of tcpi_bytes_acked / tcpi_bytes_received in struct tcp_info (Linux 4.1+)
+    const TCPI_BYTES_ACKED: usize = 120;
+    const TCPI_BYTES_RECEIVED: usize = 128;
+
+    fn align4(len: usize) -> usize {
+        (len + 3) & !3
+    }
+
+    fn u16_at(buf: &[u8], at: usize) -> u16 {
+        u16::from_ne_bytes([buf[at], buf[at + 1]])
+    }
+
+    fn u32_at(buf: &[u8], at: usize) -> u32 {
+        u32::from_ne_bytes(buf[at..at + 4].try_into().unwrap())
+    }
+
+    fn u64_at(buf: &[u8], at: usize) -> u64 {
+        u64::from_ne_bytes(buf[at..at + 8].try_into().unwrap())
+    }
+
+    // nlmsghdr followed by inet_diag_req_v2 asking for every state plus tcp_info
+    fn dump_request(family: u8, seq: u32) -> [u8; NLMSG_HDR_LEN + INET_DIAG_REQ_LEN] {
+        let mut req = [0u8; NLMSG_HDR_LEN + INET_DIAG_REQ_LEN];
+        req[0..4].copy_from_slice(&((NLMSG_HDR_LEN + INET_DIAG_REQ_LEN) as u32).to_ne_bytes());
+        req[4..6].copy_from_slice(&SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+        req[6..8].copy_from_slice(&((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+        req[8..12].copy_from_slice(&seq.to_ne_bytes());
+        req[16] = family;
+        req[17] = libc::IPPROTO_TCP as u8;
+        req[18] = 1 << (INET_DIAG_INFO - 1);
+        req[20..24].copy_from_slice(&u32::MAX.to_ne_bytes());
+        req
+    }
+
+    // inet_diag_msg followed by rtattrs; only sockets with a full tcp_info count
+    fn parse_diag_msg(msg: &[u8]) -> Option<SocketCounters> {
+        if msg.len() < INET_DIAG_MSG_LEN {
+            return None;
+        }
+        let inode = u32_at(msg, 68) as u64;
+        let mut at = INET_DIAG_MSG_LEN;
+        while at + 4 <= msg.len() {
+            let len = u16_at(msg, at) as usize;
+            if len < 4 || at + len > msg.len() {
+                break;
+            }
+            let payload = &msg[at + 4..at + len];
+            if u16_at(msg, at + 2) == INET_DIAG_INFO && payload.len() >= TCPI_BYTES_RECEIVED + 8 {
+                return Some(SocketCounters {
+                    inode,
+                    rx_bytes: u64_at(payload, TCPI_BYTES_RECEIVED),
+                    tx_bytes: u64_at(payload, TCPI_BYTES_ACKED),
+                });
+            }
+            at += align4(len);
+        }
+        None
+    }
+
+    // Returns true once the end of the dump has been reached
+    pub(super) fn parse_messages(buf: &[u8], out: &mut Vec<SocketCounters>) -> io::Result<bool> {
+        let mut at = 0;
+        while at + NLMSG_HDR_LEN <= buf.len() {
+            let len = u32_at(buf, at) as usize;
+            if len < NLMSG_HDR_LEN || at + len > buf.len() {
+                break;
+            }
+            match u16_at(buf, at + 4) as i32 {
+                libc::NLMSG_DONE => return Ok(true),
+                libc::NLMSG_ERROR => {
+                    let errno = -(u32_at(buf, at + NLMSG_HDR_LEN) as i32);
+                    return Err(io::Error::from_raw_os_error(errno));
+                }
+                _ => out.extend(parse_diag_msg(&buf[at + NLMSG_HDR_LEN..at + len])),
+            }
+            at += align4(len);
+        }
+        Ok(false)
+    }
+
+    struct Socket(i32);
+
+    impl Drop for Socket {
+        fn drop(&mut self) {
+            unsafe { libc::close(self.0) };
+        }
+    }
+
+    pub fn tcp_socket_counters() -> io::Result<Vec<SocketCounters>> {
+        let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, libc::NETLINK_SOCK_DIAG) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let socket = Socket(fd);
+        let mut kernel: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        kernel.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+
+        let mut counters = Vec::new();
+        let mut buf = vec![0u8; 32 * 1024];
+        for (seq, family) in [libc::AF_INET, libc::AF_INET6].into_iter().enumerate() {
+            let req = dump_request(family as u8, seq as u32 + 1);
+            let sent = unsafe {
+                libc::sendto(
+                    socket.0,
+                    req.as_ptr() as *const libc::c_void,
+                    req.len(),
+                    0,
+                    &kernel as *const libc::sockaddr_nl as *const libc::sockaddr,
+                    std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+                )
+            };
+            if sent < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            loop {
+                let received = unsafe { libc::recv(socket.0, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+                if received < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                if received == 0 || parse_messages(&buf[..received as usize], &mut counters)? {
+                    break;
+                }
+            }
+        }
+        Ok(counters)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        // One nlmsghdr + inet_diag_msg + INET_DIAG_INFO attribute
+        fn diag_message(inode: u32, rx: u64, tx: u64) -> Vec<u8> {
+            let mut info = vec![0u8; 232];
+            info[TCPI_BYTES_ACKED..TCPI_BYTES_ACKED + 8].copy_from_slice(&tx.to_ne_bytes());
+            info[TCPI_BYTES_RECEIVED..TCPI_BYTES_RECEIVED + 8].copy_from_slice(&rx.to_ne_bytes());
+
+            let mut msg = vec![0u8; INET_DIAG_MSG_LEN];
+            msg[68..72].copy_from_slice(&inode.to_ne_bytes());
+            msg.extend(((info.len() + 4) as u16).to_ne_bytes());
+            msg.extend(INET_DIAG_INFO.to_ne_bytes());
+            msg.extend(info);
+
+            let mut out = Vec::new();
+            out.extend(((NLMSG_HDR_LEN + msg.len()) as u32).to_ne_bytes());
+            out.extend(SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+            out.extend([0u8; 10]);
+            out.extend(msg);
+            out
+        }
+
+        #[test]
+        fn parses_tcp_info_byte_counters_until_done() {
+            let mut buf = diag_message(4242, 1_000, 2_000);
+            buf.extend(diag_message(4343, 5, 7));
+            let mut done = vec![0u8; 20];
+            done[0..4].copy_from_slice(&20u32.to_ne_bytes());
+            done[4..6].copy_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes());
+            buf.extend(done);
+
+            let mut counters = Vec::new();
+            assert!(parse_messages(&buf, &mut counters).unwrap());
+            assert_eq!(counters.len(), 2);
+            assert_eq!(counters[0], SocketCounters { inode: 4242, rx_bytes: 1_000, tx_bytes: 2_000 });
+            assert_eq!(counters[1].inode, 4343);
+        }
+
+        #[test]
+        fn request_asks_for_tcp_info_on_every_state() {
+            let req = dump_request(libc::AF_INET6 as u8, 7);
+            assert_eq!(u32_at(&req, 0) as usize, req.len());
+            assert_eq!(u16_at(&req, 4), SOCK_DIAG_BY_FAMILY);
+            assert_eq!(req[17], libc::IPPROTO_TCP as u8);
+            assert_eq!(req[18], 0b10);
+            assert_eq!(u32_at(&req, 20), u32::MAX);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn socket(inode: u64, rx_bytes: u64, tx_bytes: u64) -> SocketCounters {
+        SocketCounters { inode, rx_bytes, tx_bytes }
+    }
+
+    #[test]
+    fn attributes_socket_deltas_to_owning_processes() {
+        let owners: HashMap<u64, u32> = [(10, 100), (11, 100), (20, 200)].into_iter().collect();
+        let mut tracker = ProcessNetTracker::new();
+        let start = Instant::now();
+
+        tracker.update_from(&[socket(10, 1_000, 500), socket(20, 0, 0)], &owners, start);
+        assert_eq!(tracker.rate_for(Pid::from_u32(100)), Some(ProcessNetRate::default()));
+
+        // Socket 11 opened during the interval; socket 30 has no known owner
+        let later = [socket(10, 3_000, 1_500), socket(11, 1_000, 0), socket(20, 400, 200), socket(30, 9_999, 9_999)];
+        tracker.update_from(&later, &owners, start + Duration::from_secs(2));
+
+        let p100 = tracker.rate_for(Pid::from_u32(100)).unwrap();
+        assert_eq!(p100.rx_bytes_per_sec, 1_500.0);
+        assert_eq!(p100.tx_bytes_per_sec, 500.0);
+        let p200 = tracker.rate_for(Pid::from_u32(200)).unwrap();
+        assert_eq!(p200.rx_bytes_per_sec, 200.0);
+        assert_eq!(tracker.rate_for(Pid::from_u32(300)), Some(ProcessNetRate::default()));
+    }
+
+    #[test]
+    fn unavailable_until_first_sample() {
+        let tracker = ProcessNetTracker::new();
+        assert_eq!(tracker.rate_for(Pid::from_u32(1)), None);
+    }
+}
@@ -0,0 +1,160 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Instant;
+use sysinfo::{Pid, PidExt, ProcessExt, System, SystemExt};
+
+// How process CPU% is scaled: per-core (100% = one busy core, like top) or
+// per-machine (100% = every core busy, like Task Manager)
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CpuNormalization {
+    #[default]
+    PerCore,
+    Machine,
+}
+
+#[derive(Clone, Copy)]
+struct CpuSample {
+    // Total user + system time in clock ticks
+    cpu_ticks: u64,
+    // Process start time, used to detect PID reuse between samples
+    start_ticks: u64,
+    threads: u32,
+    at: Instant,
+}
+
+// Per-process bookkeeping carried between sampler refreshes
+pub struct ProcessTracker {
+    samples: HashMap<Pid, CpuSample>,
+    // Per-core CPU% over the last sampling window
+    cpu_usage: HashMap<Pid, f32>,
+    logical_cpus: usize,
+}
+
+impl ProcessTracker {
+    pub fn new() -> Self {
+        ProcessTracker {
+            samples: HashMap::new(),
+            cpu_usage: HashMap::new(),
+            logical_cpus: num_cpus::get().max(1),
+        }
+    }
+
+    // Called by the sampler right after the process table has been refreshed
+    pub fn update(&mut self, sys: &System) {
+        let now = Instant::now();
+        let mut samples = HashMap::with_capacity(sys.processes().len());
+        let mut cpu_usage = HashMap::with_capacity(sys.processes().len());
+
+        for (pid, proc) in sys.processes() {
+            let Some(sample) = read_cpu_sample(*pid, now) else {
+                // No /proc data for this process (or not on Linux): sysinfo's own
+                // delta is correct now that the System lives across refreshes
+                cpu_usage.insert(*pid, proc.cpu_usage());
+                continue;
+            };
+
+            if let Some(prev) = self.samples.get(pid) {
+                if prev.start_ticks == sample.start_ticks {
+                    let elapsed = now.duration_since(prev.at).as_secs_f64();
+                    let ticks = sample.cpu_ticks.saturating_sub(prev.cpu_ticks);
+                    if elapsed > 0.0 {
+                        let seconds = ticks as f64 / clock_ticks_per_second() as f64;
+                        cpu_usage.insert(*pid, (seconds / elapsed * 100.0) as f32);
+                    }
+                }
+            }
+            samples.insert(*pid, sample);
+        }
+
+        self.samples = samples;
+        self.cpu_usage = cpu_usage;
+    }
+
+    // Only known where /proc is available
+    pub fn thread_count(&self, pid: Pid) -> Option<u32> {
+        self.samples.get(&pid).map(|s| s.threads)
+    }
+
+    // Processes seen for the first time report 0% until the next refresh
+    pub fn cpu_usage(&self, pid: Pid, normalization: CpuNormalization) -> f32 {
+        let usage = self.cpu_usage.get(&pid).copied().unwrap_or(0.0);
+        match normalization {
+            CpuNormalization::PerCore => usage,
+            CpuNormalization::Machine => usage / self.logical_cpus as f32,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_cpu_sample(pid: Pid, at: Instant) -> Option<CpuSample> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid.as_u32())).ok()?;
+    let (cpu_ticks, start_ticks, threads) = parse_stat(&stat)?;
+    Some(CpuSample {
+        cpu_ticks,
+        start_ticks,
+        threads,
+        at,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_cpu_sample(_pid: Pid, _at: Instant) -> Option<CpuSample> {
+    None
+}
+
+// Returns (utime + stime, starttime, num_threads) from a /proc/<pid>/stat line.
+// The comm field may contain spaces and parentheses, so split after the last ')'.
+#[cfg(target_os = "linux")]
+fn parse_stat(stat: &str) -> Option<(u64, u64, u32)> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    // Fields after comm start at index 3 of proc(5), so utime (14) is fields[11]
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+    let num_threads = fields.get(17)?.parse::<u32>().ok()?;
+    let starttime = fields.get(19)?.parse::<u64>().ok()?;
+    Some((utime + stime, starttime, num_threads))
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn clock_ticks_per_second() -> u64 {
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as u64
+    } else {
+        100
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn clock_ticks_per_second() -> u64 {
+    100
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture(name: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proc_pid").join(name);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn parses_cpu_ticks_start_time_and_threads() {
+        let stat = fixture("task_stat");
+        assert_eq!(parse_stat(&stat), Some((1830, 98211, 31)));
+
+        // Only the last ')' ends comm: a process named "(a) b)" shows up as "((a) b))"
+        let tricky = stat.replace("(Web Content)", "((a) b))");
+        assert_eq!(parse_stat(&tricky), Some((1830, 98211, 31)));
+    }
+
+    #[test]
+    fn rejects_truncated_lines() {
+        assert_eq!(parse_stat("4321 (sh) S 1 1 1"), None);
+        assert_eq!(parse_stat("4321 sh S"), None);
+    }
+}
@@ -0,0 +1,142 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::ProcessInfo;
+
+#[derive(Serialize)]
+pub struct ProcessTreeNode {
+    #[serde(flatten)]
+    pub process: ProcessInfo,
+    // Totals for this process and everything below it
+    pub subtree_cpu: f32,
+    pub subtree_memory_bytes: u64, // PSS where available, RSS otherwise
+    pub subtree_count: usize,
+    pub children: Vec<ProcessTreeNode>,
+}
+
+// Roots are processes whose parent is unknown or no longer running (pid 1, kthreadd,
+// orphans whose parent exited between refreshes). Children are ordered by pid.
+pub fn build_tree(processes: Vec<ProcessInfo>) -> Vec<ProcessTreeNode> {
+    let pids: HashSet<i32> = processes.iter().map(|p| p.pid).collect();
+    let mut by_pid: HashMap<i32, ProcessInfo> = HashMap::with_capacity(processes.len());
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    let mut roots = Vec::new();
+
+    for proc in processes {
+        match proc.ppid.filter(|ppid| *ppid != proc.pid && pids.contains(ppid)) {
+            Some(ppid) => children.entry(ppid).or_default().push(proc.pid),
+            None => roots.push(proc.pid),
+        }
+        by_pid.insert(proc.pid, proc);
+    }
+    for list in children.values_mut() {
+        list.sort_unstable();
+    }
+    roots.sort_unstable();
+
+    let mut tree: Vec<ProcessTreeNode> = roots
+        .into_iter()
+        .filter_map(|pid| build_node(pid, &mut by_pid, &children))
+        .collect();
+
+    // Anything left over sits on a parent cycle (pid reuse caught mid-refresh);
+    // surface those as roots rather than dropping them
+    let mut leftover: Vec<i32> = by_pid.keys().copied().collect();
+    leftover.sort_unstable();
+    for pid in leftover {
+        if let Some(node) = build_node(pid, &mut by_pid, &children) {
+            tree.push(node);
+        }
+    }
+    tree
+}
+
+// Taking each process out of `by_pid` as it is placed guarantees termination
+fn build_node(
+    pid: i32,
+    by_pid: &mut HashMap<i32, ProcessInfo>,
+    children: &HashMap<i32, Vec<i32>>,
+) -> Option<ProcessTreeNode> {
+    let process = by_pid.remove(&pid)?;
+    let kids: Vec<ProcessTreeNode> = children
+        .get(&pid)
+        .map(|list| list.iter().filter_map(|&c| build_node(c, by_pid, children)).collect())
+        .unwrap_or_default();
+
+    Some(ProcessTreeNode {
+        subtree_cpu: process.cpu + kids.iter().map(|k| k.subtree_cpu).sum::<f32>(),
+        subtree_memory_bytes: process.pss_bytes.unwrap_or(process.rss_bytes)
+            + kids.iter().map(|k| k.subtree_memory_bytes).sum::<u64>(),
+        subtree_count: 1 + kids.iter().map(|k| k.subtree_count).sum::<usize>(),
+        process,
+        children: kids,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proc(pid: i32, ppid: Option<i32>, cpu: f32, memory: u64) -> ProcessInfo {
+        ProcessInfo {
+            name: format!("p{}", pid),
+            cpu,
+            rss_bytes: memory,
+            pid,
+            ppid,
+            state: "sleeping".to_string(),
+            threads: Some(1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn nests_children_and_aggregates_subtrees() {
+        let tree = build_tree(vec![
+            proc(300, Some(200), 5.0, 300),
+            proc(1, None, 0.5, 10),
+            proc(200, Some(1), 10.0, 200),
+            proc(201, Some(1), 1.0, 50),
+            proc(301, Some(200), 2.5, 100),
+        ]);
+
+        assert_eq!(tree.len(), 1);
+        let init = &tree[0];
+        assert_eq!(init.process.pid, 1);
+        assert_eq!(init.subtree_count, 5);
+        assert_eq!(init.subtree_memory_bytes, 660);
+        assert_eq!(init.subtree_cpu, 19.0);
+
+        let app = &init.children[0];
+        assert_eq!(app.process.pid, 200);
+        assert_eq!(app.children.iter().map(|c| c.process.pid).collect::<Vec<_>>(), vec![300, 301]);
+        assert_eq!(app.subtree_cpu, 17.5);
+        assert_eq!(app.subtree_memory_bytes, 600);
+    }
+
+    #[test]
+    fn orphans_and_cycles_become_roots() {
+        let tree = build_tree(vec![
+            proc(10, Some(999), 1.0, 1), // parent already exited
+            proc(20, Some(21), 1.0, 1),
+            proc(21, Some(20), 1.0, 1),
+        ]);
+        let roots: Vec<i32> = tree.iter().map(|n| n.process.pid).collect();
+        assert_eq!(roots, vec![10, 20]);
+        assert_eq!(tree[1].subtree_count, 2);
+    }
+
+    #[test]
+    fn subtree_memory_prefers_pss() {
+        let mut browser = proc(100, None, 0.0, 400);
+        browser.pss_bytes = Some(250);
+        // Shared pages would be counted in every renderer's RSS
+        let mut renderer = proc(101, Some(100), 0.0, 300);
+        renderer.pss_bytes = Some(120);
+        let unreadable = proc(102, Some(100), 0.0, 40);
+
+        let tree = build_tree(vec![browser, renderer, unreadable]);
+        assert_eq!(tree[0].subtree_memory_bytes, 250 + 120 + 40);
+        assert_eq!(tree[0].children[0].subtree_memory_bytes, 120);
+    }
+}
@@ -0,0 +1,355 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use sysinfo::{PidExt, ProcessExt, System, SystemExt, UserExt};
+
+use crate::procctl::{ControlError, ControlErrorKind, ProcessTarget};
+
+// Processes that kill/suspend/renice requests must never touch
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectionRule {
+    Pid(i32),
+    Name(String),   // process name or executable file name
+    Exe(String),    // full executable path
+    User(String),   // owning user name
+    Cgroup(String), // cgroup path prefix, e.g. "/system.slice"
+}
+
+impl std::fmt::Display for ProtectionRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProtectionRule::Pid(pid) => write!(f, "pid {}", pid),
+            ProtectionRule::Name(name) => write!(f, "name \"{}\"", name),
+            ProtectionRule::Exe(exe) => write!(f, "executable {}", exe),
+            ProtectionRule::User(user) => write!(f, "user {}", user),
+            ProtectionRule::Cgroup(cgroup) => write!(f, "cgroup {}", cgroup),
+        }
+    }
+}
+
+// What the rules are matched against
+#[derive(Default, Debug)]
+pub struct ProcessIdentity {
+    pub pid: i32,
+    pub name: Option<String>,
+    pub exe: Option<String>,
+    pub user: Option<String>,
+    pub cgroups: Vec<String>,
+}
+
+// Linux truncates names to 15 bytes, so also accept the executable's file name
+pub(crate) fn name_matches(name: Option<&str>, exe: Option<&str>, wanted: &str) -> bool {
+    name == Some(wanted)
+        || exe
+            .and_then(|exe| Path::new(exe).file_name())
+            .is_some_and(|file| file == wanted)
+}
+
+impl ProtectionRule {
+    pub fn matches(&self, process: &ProcessIdentity) -> bool {
+        match self {
+            ProtectionRule::Pid(pid) => process.pid == *pid,
+            ProtectionRule::Name(name) => name_matches(process.name.as_deref(), process.exe.as_deref(), name),
+            ProtectionRule::Exe(exe) => process.exe.as_deref() == Some(exe.as_str()),
+            ProtectionRule::User(user) => process.user.as_deref() == Some(user.as_str()),
+            ProtectionRule::Cgroup(prefix) => {
+                let prefix = prefix.trim_end_matches('/');
+                process.cgroups.iter().any(|path| {
+                    prefix.is_empty()
+                        || path == prefix
+                        || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
+                })
+            }
+        }
+    }
+}
+
+// Init, the login/session manager and display server. This app itself is
+// protected by every policy, see ProtectionPolicy::with_rules.
+pub fn default_rules() -> Vec<ProtectionRule> {
+    #[cfg(target_os = "linux")]
+    let names = [
+        "systemd-logind",
+        "gdm",
+        "sddm",
+        "lightdm",
+        "gnome-session-binary",
+        "gnome-shell",
+        "ksmserver",
+        "kwin_wayland",
+        "kwin_x11",
+        "xfce4-session",
+        "Xorg",
+        "Xwayland",
+    ];
+    #[cfg(target_os = "macos")]
+    let names = ["launchd", "loginwindow", "WindowServer"];
+    #[cfg(target_os = "windows")]
+    let names = [
+        "System",
+        "smss.exe",
+        "csrss.exe",
+        "wininit.exe",
+        "winlogon.exe",
+        "services.exe",
+        "lsass.exe",
+        "dwm.exe",
+    ];
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    let names: [&str; 0] = [];
+
+    let mut rules = Vec::new();
+    #[cfg(unix)]
+    rules.push(ProtectionRule::Pid(1));
+    rules.extend(names.iter().map(|name| ProtectionRule::Name(name.to_string())));
+    rules
+}
+
+fn own_pid_rule() -> ProtectionRule {
+    ProtectionRule::Pid(std::process::id() as i32)
+}
+
+// On-disk format, matching the watchdog rules file
+#[derive(Deserialize, Serialize, Default)]
+struct ProtectionFile {
+    rules: Vec<ProtectionRule>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProtectionStatus {
+    pub rules_path: Option<String>,
+    pub load_error: Option<String>, // rules are not saved while this is set
+}
+
+pub struct ProtectionPolicy {
+    rules: Vec<ProtectionRule>,
+    path: Option<PathBuf>,
+    load_error: Option<String>,
+}
+
+pub type ProtectionState = Arc<Mutex<ProtectionPolicy>>;
+
+impl Default for ProtectionPolicy {
+    fn default() -> Self {
+        ProtectionPolicy::with_rules(default_rules())
+    }
+}
+
+impl ProtectionPolicy {
+    // Whatever the rules say, this app's own pid stays protected
+    pub fn with_rules(rules: Vec<ProtectionRule>) -> Self {
+        let mut policy = ProtectionPolicy {
+            rules: Vec::new(),
+            path: None,
+            load_error: None,
+        };
+        policy.replace_rules(rules);
+        policy
+    }
+
+    fn replace_rules(&mut self, mut rules: Vec<ProtectionRule>) {
+        let own = own_pid_rule();
+        if !rules.contains(&own) {
+            rules.insert(0, own);
+        }
+        self.rules = rules;
+    }
+
+    pub fn rules(&self) -> &[ProtectionRule] {
+        &self.rules
+    }
+
+    // Rules are saved back to `path` whenever they change. Without a file the
+    // defaults apply; a file that fails to load is left alone, and saving is
+    // refused so it cannot be overwritten.
+    pub fn load(&mut self, path: PathBuf) -> Result<(), String> {
+        let loaded = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str::<ProtectionFile>(&text)
+                .map(|file| file.rules)
+                .map_err(|e| format!("Invalid protection rules in {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(default_rules()),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        self.path = Some(path);
+        self.load_error = loaded.as_ref().err().cloned();
+        self.replace_rules(loaded?);
+        Ok(())
+    }
+
+    pub fn status(&self) -> ProtectionStatus {
+        ProtectionStatus {
+            rules_path: self.path.as_ref().map(|p| p.display().to_string()),
+            load_error: self.load_error.clone(),
+        }
+    }
+
+    // The own-pid rule is left out: the pid is different on the next run
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let own = own_pid_rule();
+        let file = ProtectionFile {
+            rules: self.rules.iter().filter(|rule| **rule != own).cloned().collect(),
+        };
+        let text = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn set_rules(&mut self, rules: Vec<ProtectionRule>) -> Result<(), String> {
+        if let Some(e) = &self.load_error {
+            return Err(format!("{}; fix or remove the file before changing rules", e));
+        }
+        self.replace_rules(rules);
+        self.save()
+    }
+
+    pub fn reset(&mut self) -> Result<(), String> {
+        self.set_rules(default_rules())
+    }
+
+    pub fn matching_rule(&self, process: &ProcessIdentity) -> Option<&ProtectionRule> {
+        self.rules.iter().find(|rule| rule.matches(process))
+    }
+
+    // Fails with a Protected error if the target, or any member of a target
+    // group, matches a rule. Processes that have already exited pass; the
+    // action itself then reports them as not found.
+    pub fn check(&self, sys: &mut System, target: ProcessTarget) -> Result<(), ControlError> {
+        let pids = match target {
+            ProcessTarget::Pid(pid) => vec![pid],
+            #[cfg(target_os = "linux")]
+            ProcessTarget::Group(pgid) => crate::procctl::group_members(pgid),
+            #[cfg(not(target_os = "linux"))]
+            ProcessTarget::Group(pgid) => vec![pgid],
+        };
+        for pid in pids {
+            let process = identify(sys, pid);
+            if let Some(rule) = self.matching_rule(&process) {
+                let name = process.name.as_deref().unwrap_or("unknown");
+                return Err(ControlError::new(
+                    ControlErrorKind::Protected,
+                    format!("Process {} ({}) is protected by the {} rule", pid, name, rule),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Refreshes the process first so a recycled pid is judged by its new owner
+pub fn identify(sys: &mut System, pid: i32) -> ProcessIdentity {
+    let mut identity = ProcessIdentity {
+        pid,
+        ..Default::default()
+    };
+    if pid <= 0 {
+        return identity;
+    }
+    let sys_pid = sysinfo::Pid::from_u32(pid as u32);
+    sys.refresh_process(sys_pid);
+    if let Some(proc) = sys.process(sys_pid) {
+        identity.name = Some(proc.name().to_string());
+        identity.exe = Some(proc.exe().to_string_lossy().into_owned()).filter(|exe| !exe.is_empty());
+        identity.user = proc
+            .user_id()
+            .and_then(|uid| sys.get_user_by_id(uid))
+            .map(|u| u.name().to_string());
+    }
+    #[cfg(target_os = "linux")]
+    if let Ok(text) = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)) {
+        identity.cgroups = crate::procdetails::parse_cgroup(&text).into_iter().map(|c| c.path).collect();
+    }
+    identity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xorg() -> ProcessIdentity {
+        ProcessIdentity {
+            pid: 1432,
+            name: Some("Xorg".to_string()),
+            exe: Some("/usr/lib/xorg/Xorg".to_string()),
+            user: Some("root".to_string()),
+            cgroups: vec!["/system.slice/display-manager.service".to_string()],
+        }
+    }
+
+    #[test]
+    fn matches_each_rule_kind() {
+        let process = xorg();
+        assert!(ProtectionRule::Pid(1432).matches(&process));
+        assert!(!ProtectionRule::Pid(1).matches(&process));
+        assert!(ProtectionRule::Name("Xorg".to_string()).matches(&process));
+        assert!(ProtectionRule::Exe("/usr/lib/xorg/Xorg".to_string()).matches(&process));
+        assert!(!ProtectionRule::Exe("/usr/bin/Xorg".to_string()).matches(&process));
+        assert!(ProtectionRule::User("root".to_string()).matches(&process));
+        assert!(ProtectionRule::Cgroup("/system.slice".to_string()).matches(&process));
+        assert!(ProtectionRule::Cgroup("/system.slice/".to_string()).matches(&process));
+        // Prefixes only match whole path components
+        assert!(!ProtectionRule::Cgroup("/system.sl".to_string()).matches(&process));
+        assert!(!ProtectionRule::Cgroup("/user.slice".to_string()).matches(&process));
+    }
+
+    #[test]
+    fn names_fall_back_to_the_executable_file_name() {
+        let session = ProcessIdentity {
+            pid: 2001,
+            name: Some("gnome-session-b".to_string()),
+            exe: Some("/usr/libexec/gnome-session-binary".to_string()),
+            ..Default::default()
+        };
+        assert!(ProtectionRule::Name("gnome-session-binary".to_string()).matches(&session));
+        assert!(!ProtectionRule::Name("gnome-session".to_string()).matches(&session));
+    }
+
+    #[test]
+    fn default_policy_protects_this_process() {
+        let policy = ProtectionPolicy::default();
+        let own = std::process::id() as i32;
+        let mut sys = System::new();
+        let err = policy.check(&mut sys, ProcessTarget::Pid(own)).unwrap_err();
+        assert_eq!(err.kind, ControlErrorKind::Protected);
+        assert!(err.message.contains(&format!("pid {}", own)));
+
+        // Even an empty rule list keeps this process protected
+        let empty = ProtectionPolicy::with_rules(Vec::new());
+        assert_eq!(empty.rules(), &[ProtectionRule::Pid(own)]);
+        assert!(empty.check(&mut sys, ProcessTarget::Pid(own)).is_err());
+        assert!(empty.check(&mut sys, ProcessTarget::Pid(i32::MAX)).is_ok());
+    }
+
+    #[test]
+    fn persists_rules_without_the_own_pid() {
+        let path = std::env::temp_dir().join(format!("sys-dock-protection-{}.json", std::process::id()));
+        let mut policy = ProtectionPolicy::default();
+        policy.load(path.clone()).unwrap();
+        assert_eq!(policy.rules().len(), default_rules().len() + 1);
+
+        let rules = vec![ProtectionRule::Name("postgres".to_string())];
+        policy.set_rules(rules.clone()).unwrap();
+        let saved: ProtectionFile = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.rules, rules);
+
+        let mut reloaded = ProtectionPolicy::default();
+        reloaded.load(path.clone()).unwrap();
+        assert_eq!(
+            reloaded.rules(),
+            &[ProtectionRule::Pid(std::process::id() as i32), ProtectionRule::Name("postgres".to_string())]
+        );
+
+        fs::write(&path, "not json").unwrap();
+        assert!(reloaded.load(path.clone()).is_err());
+        assert!(reloaded.status().load_error.is_some());
+        assert!(reloaded.set_rules(Vec::new()).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "not json");
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, System, SystemExt};
+
+use crate::cgroups::{CgroupMembership, CgroupTracker};
+use crate::diskstats::DiskIoTracker;
+use crate::netrates::NetRateTracker;
+use crate::procio::{self, IoSample, ProcessIoTracker};
+use crate::procmem::{MemoryBreakdown, ProcessMemoryTracker};
+use crate::procnet::{ProcessNetTracker, SocketSample};
+use crate::proctrack::ProcessTracker;
+use crate::protection::ProtectionState;
+use crate::sensors::{self, Sensor};
+use crate::watchdog::Watchdog;
+
+// Refresh cadence for each subsystem, in milliseconds
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SamplerConfig {
+    pub cpu_ms: u64,
+    pub memory_ms: u64,
+    pub processes_ms: u64,
+    pub disks_ms: u64,
+    pub disk_io_ms: u64,
+    pub networks_ms: u64,
+    pub sensors_ms: u64,
+}
+
+impl Default for SamplerConfig {
+    fn default() -> Self {
+        SamplerConfig {
+            cpu_ms: 1000,
+            memory_ms: 1000,
+            processes_ms: 2000,
+            disks_ms: 10000,
+            disk_io_ms: 1000,
+            networks_ms: 1000,
+            sensors_ms: 2000,
+        }
+    }
+}
+
+impl SamplerConfig {
+    // Anything faster than this makes sysinfo's CPU deltas too noisy to be useful
+    const MIN_INTERVAL_MS: u64 = 200;
+
+    fn clamped(mut self) -> Self {
+        for ms in [
+            &mut self.cpu_ms,
+            &mut self.memory_ms,
+            &mut self.processes_ms,
+            &mut self.disks_ms,
+            &mut self.disk_io_ms,
+            &mut self.networks_ms,
+            &mut self.sensors_ms,
+        ] {
+            *ms = (*ms).max(Self::MIN_INTERVAL_MS);
+        }
+        self
+    }
+
+    fn shortest(&self) -> Duration {
+        let ms = [
+            self.cpu_ms,
+            self.memory_ms,
+            self.processes_ms,
+            self.disks_ms,
+            self.disk_io_ms,
+            self.networks_ms,
+            self.sensors_ms,
+        ]
+        .into_iter()
+        .min()
+        .unwrap_or(Self::MIN_INTERVAL_MS);
+        Duration::from_millis(ms)
+    }
+}
+
+#[derive(Default)]
+struct LastRefresh {
+    cpu: Option<Instant>,
+    memory: Option<Instant>,
+    processes: Option<Instant>,
+    disks: Option<Instant>,
+    disk_io: Option<Instant>,
+    networks: Option<Instant>,
+    sensors: Option<Instant>,
+}
+
+// One long-lived System shared by every command, refreshed in the background
+pub struct Sampler {
+    pub sys: System,
+    pub processes: ProcessTracker,
+    pub process_net: ProcessNetTracker,
+    pub process_io: ProcessIoTracker,
+    pub process_memory: ProcessMemoryTracker,
+    pub cgroups: CgroupTracker,
+    pub disk_io: DiskIoTracker,
+    pub network: NetRateTracker,
+    pub sensors: Vec<Sensor>,
+    pub watchdog: Watchdog,
+    protection: ProtectionState,
+    config: SamplerConfig,
+    last: LastRefresh,
+}
+
+pub type SamplerState = Arc<Mutex<Sampler>>;
+
+// The processes seen by a refresh, taken under the sampler lock
+pub struct ProcessSnapshot {
+    pids: Vec<Pid>,
+    io: Vec<(Pid, IoSample)>,
+}
+
+// The per-process files behind the net, I/O, memory and cgroup trackers.
+// Walking every fd table and reading smaps_rollup takes a while with many
+// processes, so this is gathered without holding the sampler lock.
+pub struct ProcessReads {
+    at: Instant,
+    sockets: Option<SocketSample>,
+    io: Vec<(Pid, IoSample)>,
+    memory: HashMap<Pid, MemoryBreakdown>,
+    cgroups: HashMap<Pid, CgroupMembership>,
+}
+
+impl ProcessSnapshot {
+    fn take(sys: &System) -> Self {
+        ProcessSnapshot {
+            pids: sys.processes().keys().copied().collect(),
+            io: procio::snapshot(sys),
+        }
+    }
+
+    pub fn read(self) -> ProcessReads {
+        ProcessReads {
+            at: Instant::now(),
+            sockets: ProcessNetTracker::read(),
+            io: ProcessIoTracker::read(self.io),
+            memory: ProcessMemoryTracker::read(&self.pids),
+            cgroups: CgroupTracker::read(&self.pids),
+        }
+    }
+}
+
+fn due(last: Option<Instant>, interval_ms: u64, now: Instant) -> bool {
+    match last {
+        Some(t) => now.duration_since(t) >= Duration::from_millis(interval_ms),
+        None => true,
+    }
+}
+
+impl Sampler {
+    pub fn new(config: SamplerConfig, protection: ProtectionState) -> Self {
+        let mut sys = System::new_all();
+        sys.refresh_disks_list();
+        sys.refresh_networks_list();
+        let mut processes = ProcessTracker::new();
+        processes.update(&sys);
+        let mut disk_io = DiskIoTracker::new();
+        disk_io.update();
+        let mut network = NetRateTracker::new();
+        network.update(&sys);
+        let reads = ProcessSnapshot::take(&sys).read();
+        let mut sampler = Sampler {
+            sys,
+            processes,
+            process_net: ProcessNetTracker::new(),
+            process_io: ProcessIoTracker::new(),
+            process_memory: ProcessMemoryTracker::new(),
+            cgroups: CgroupTracker::new(),
+            disk_io,
+            network,
+            sensors: sensors::read_sensors(Path::new(sensors::SYSFS_ROOT)),
+            watchdog: Watchdog::new(),
+            protection,
+            config: config.clamped(),
+            last: LastRefresh::default(),
+        };
+        sampler.store(reads);
+        sampler
+    }
+
+    pub fn config(&self) -> &SamplerConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: SamplerConfig) {
+        self.config = config.clamped();
+    }
+
+    // Refresh every subsystem whose interval has elapsed. When the process list
+    // was refreshed, the returned snapshot is to be read and handed to store().
+    pub fn tick(&mut self) -> Option<ProcessSnapshot> {
+        let now = Instant::now();
+        let mut snapshot = None;
+
+        if due(self.last.cpu, self.config.cpu_ms, now) {
+            self.sys.refresh_cpu();
+            self.last.cpu = Some(now);
+        }
+        if due(self.last.memory, self.config.memory_ms, now) {
+            self.sys.refresh_memory();
+            self.last.memory = Some(now);
+        }
+        if due(self.last.processes, self.config.processes_ms, now) {
+            self.sys.refresh_processes();
+            self.processes.update(&self.sys);
+            snapshot = Some(ProcessSnapshot::take(&self.sys));
+            // Lock order is sampler, then protection policy
+            let policy = self.protection.lock().unwrap();
+            self.watchdog.evaluate(&mut self.sys, &self.processes, &policy);
+            drop(policy);
+            self.last.processes = Some(now);
+        }
+        if due(self.last.disks, self.config.disks_ms, now) {
+            // Pick up newly mounted volumes as well as updated usage
+            self.sys.refresh_disks_list();
+            self.sys.refresh_disks();
+            self.last.disks = Some(now);
+        }
+        if due(self.last.disk_io, self.config.disk_io_ms, now) {
+            self.disk_io.update();
+            self.last.disk_io = Some(now);
+        }
+        if due(self.last.networks, self.config.networks_ms, now) {
+            self.sys.refresh_networks_list();
+            self.sys.refresh_networks();
+            self.network.update(&self.sys);
+            self.last.networks = Some(now);
+        }
+        if due(self.last.sensors, self.config.sensors_ms, now) {
+            self.sensors = sensors::read_sensors(Path::new(sensors::SYSFS_ROOT));
+            self.last.sensors = Some(now);
+        }
+        snapshot
+    }
+
+    pub fn store(&mut self, reads: ProcessReads) {
+        self.process_net.apply(reads.sockets, reads.at);
+        self.process_io.apply(reads.io, reads.at);
+        self.process_memory.apply(reads.memory);
+        self.cgroups.apply(reads.cgroups);
+    }
+}
+
+pub fn spawn(state: SamplerState) -> thread::JoinHandle<()> {
+    thread::spawn(move || loop {
+        let (wait, snapshot) = {
+            let mut sampler = state.lock().unwrap();
+            let snapshot = sampler.tick();
+            (sampler.config.shortest(), snapshot)
+        };
+        // Commands keep being served from the previous reads meanwhile
+        if let Some(snapshot) = snapshot {
+            let reads = snapshot.read();
+            state.lock().unwrap().store(reads);
+        }
+        // Wake up at a fraction of the fastest cadence so every subsystem stays close to schedule
+        thread::sleep((wait / 4).max(Duration::from_millis(50)));
+    })
+}
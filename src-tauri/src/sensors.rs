@@ -0,0 +1,300 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+pub const SYSFS_ROOT: &str = "/sys";
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorKind {
+    Temperature, // °C
+    Fan,         // RPM
+    Voltage,     // V
+    Power,       // W
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Sensor {
+    pub chip: String,   // hwmon "name" or thermal zone "type"
+    pub source: String, // e.g. "hwmon2" or "thermal_zone0"
+    pub kind: SensorKind,
+    pub label: Option<String>,
+    pub value: f64,
+    pub max: Option<f64>,
+    pub critical: Option<f64>,
+}
+
+impl SensorKind {
+    // sysfs file prefix and the divisor that converts raw values to display units
+    fn hwmon_prefix(self) -> (&'static str, f64) {
+        match self {
+            SensorKind::Temperature => ("temp", 1000.0),  // millidegrees
+            SensorKind::Fan => ("fan", 1.0),              // RPM
+            SensorKind::Voltage => ("in", 1000.0),        // millivolts
+            SensorKind::Power => ("power", 1_000_000.0),  // microwatts
+        }
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn read_scaled(path: &Path, divisor: f64) -> Option<f64> {
+    read_trimmed(path)?.parse::<f64>().ok().map(|v| v / divisor)
+}
+
+fn sorted_entries(dir: &Path) -> Vec<std::path::PathBuf> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .map(|rd| rd.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+// Every channel of every hwmon chip and thermal zone under `sysfs_root` (normally "/sys")
+pub fn read_sensors(sysfs_root: &Path) -> Vec<Sensor> {
+    let mut sensors = read_hwmon(sysfs_root);
+    sensors.extend(read_thermal_zones(sysfs_root));
+    sensors
+}
+
+fn read_hwmon(sysfs_root: &Path) -> Vec<Sensor> {
+    let mut sensors = Vec::new();
+
+    for chip_dir in sorted_entries(&sysfs_root.join("class/hwmon")) {
+        let source = chip_dir.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let chip = read_trimmed(&chip_dir.join("name")).unwrap_or_else(|| source.clone());
+
+        for kind in [SensorKind::Temperature, SensorKind::Fan, SensorKind::Voltage, SensorKind::Power] {
+            let (prefix, divisor) = kind.hwmon_prefix();
+
+            let mut channels: Vec<u32> = sorted_entries(&chip_dir)
+                .iter()
+                .filter_map(|p| {
+                    let name = p.file_name()?.to_str()?;
+                    let rest = name.strip_prefix(prefix)?;
+                    let (index, suffix) = rest.split_once('_')?;
+                    // Power chips report either an instantaneous or an averaged value
+                    let is_value = suffix == "input" || (kind == SensorKind::Power && suffix == "average");
+                    if is_value { index.parse::<u32>().ok() } else { None }
+                })
+                .collect();
+            channels.sort_unstable();
+            channels.dedup();
+
+            for index in channels {
+                let file = |suffix: &str| chip_dir.join(format!("{}{}_{}", prefix, index, suffix));
+                let Some(value) = read_scaled(&file("input"), divisor)
+                    .or_else(|| read_scaled(&file("average"), divisor))
+                else {
+                    continue;
+                };
+                let max = match kind {
+                    SensorKind::Power => read_scaled(&file("cap"), divisor).or_else(|| read_scaled(&file("max"), divisor)),
+                    _ => read_scaled(&file("max"), divisor),
+                };
+                sensors.push(Sensor {
+                    chip: chip.clone(),
+                    source: source.clone(),
+                    kind,
+                    label: read_trimmed(&file("label")),
+                    value,
+                    max,
+                    critical: read_scaled(&file("crit"), divisor),
+                });
+            }
+        }
+    }
+
+    sensors
+}
+
+fn read_thermal_zones(sysfs_root: &Path) -> Vec<Sensor> {
+    let mut sensors = Vec::new();
+
+    for zone_dir in sorted_entries(&sysfs_root.join("class/thermal")) {
+        let source = zone_dir.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        if !source.starts_with("thermal_zone") {
+            continue;
+        }
+        let Some(value) = read_scaled(&zone_dir.join("temp"), 1000.0) else {
+            continue;
+        };
+
+        // Trip points are numbered; the one typed "critical" triggers shutdown
+        let mut critical = None;
+        for trip in 0.. {
+            let Some(trip_type) = read_trimmed(&zone_dir.join(format!("trip_point_{}_type", trip))) else {
+                break;
+            };
+            if trip_type == "critical" {
+                critical = read_scaled(&zone_dir.join(format!("trip_point_{}_temp", trip)), 1000.0);
+            }
+        }
+
+        sensors.push(Sensor {
+            chip: read_trimmed(&zone_dir.join("type")).unwrap_or_else(|| source.clone()),
+            source,
+            kind: SensorKind::Temperature,
+            label: None,
+            value,
+            max: None,
+            critical,
+        });
+    }
+
+    sensors
+}
+
+// How likely a temperature channel is to be the CPU package reading. Only known
+// CPU drivers, and channels on other chips labelled as CPU (Super I/O, laptop
+// EC drivers), qualify; drives, GPUs, Wi-Fi cards, DIMMs and the like get None.
+fn cpu_package_score(sensor: &Sensor) -> Option<u32> {
+    if sensor.kind != SensorKind::Temperature {
+        return None;
+    }
+    let chip = sensor.chip.to_lowercase();
+    let label = sensor.label.as_deref().unwrap_or("").to_lowercase();
+
+    match chip.as_str() {
+        // Intel: "Package id 0" beats individual "Core N" readings
+        "coretemp" if label.starts_with("package") => Some(100),
+        "coretemp" if label.is_empty() => Some(60),
+        "coretemp" => Some(50),
+        // AMD: Tdie is the real die temperature, Tctl may carry a fan-curve offset
+        "k10temp" | "zenpower" if label == "tdie" => Some(95),
+        "k10temp" | "zenpower" if label == "tctl" || label.is_empty() => Some(90),
+        "k10temp" | "zenpower" => Some(40),
+        "x86_pkg_temp" => Some(85),
+        // ARM SoCs usually expose a cpu-specific zone
+        c if c.contains("cpu") => Some(70),
+        "soc_thermal" => Some(30),
+        _ if label.contains("cpu") => Some(20),
+        "acpitz" => Some(10),
+        _ => None,
+    }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn cpu_package_temperature(sensors: &[Sensor]) -> Option<f32> {
+    sensors
+        .iter()
+        .filter_map(|s| cpu_package_score(s).map(|score| (score, s)))
+        // max_by_key keeps the last of equal maxima, so reverse to prefer the first
+        .rev()
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, s)| s.value as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs")
+    }
+
+    fn temp(chip: &str, label: Option<&str>, value: f64) -> Sensor {
+        Sensor {
+            chip: chip.to_string(),
+            source: "hwmon0".to_string(),
+            kind: SensorKind::Temperature,
+            label: label.map(str::to_string),
+            value,
+            max: None,
+            critical: None,
+        }
+    }
+
+    fn find<'a>(sensors: &'a [Sensor], chip: &str, label: Option<&str>) -> &'a Sensor {
+        sensors
+            .iter()
+            .find(|s| s.chip == chip && s.label.as_deref() == label)
+            .unwrap()
+    }
+
+    #[test]
+    fn enumerates_all_hwmon_channel_kinds() {
+        let sensors = read_sensors(&fixture_root());
+
+        let package = find(&sensors, "coretemp", Some("Package id 0"));
+        assert_eq!(package.value, 52.0);
+        assert_eq!(package.max, Some(100.0));
+        assert_eq!(package.critical, Some(100.0));
+
+        let nvme = find(&sensors, "nvme", Some("Composite"));
+        assert_eq!(nvme.critical, Some(84.85));
+
+        let fans: Vec<_> = sensors.iter().filter(|s| s.kind == SensorKind::Fan).collect();
+        assert_eq!(fans.len(), 2);
+        assert_eq!(fans[0].value, 1250.0);
+
+        let vcore = find(&sensors, "nct6775", Some("Vcore"));
+        assert_eq!(vcore.kind, SensorKind::Voltage);
+        assert_eq!(vcore.value, 0.912);
+        assert_eq!(vcore.max, Some(1.744));
+
+        let ppt = find(&sensors, "amdgpu", Some("PPT"));
+        assert_eq!(ppt.kind, SensorKind::Power);
+        assert_eq!(ppt.value, 35.0);
+        assert_eq!(ppt.max, Some(150.0));
+    }
+
+    #[test]
+    fn reads_thermal_zones_with_critical_trip_point() {
+        let sensors = read_sensors(&fixture_root());
+        let zones: Vec<_> = sensors.iter().filter(|s| s.source.starts_with("thermal_zone")).collect();
+        assert_eq!(zones.len(), 2);
+
+        let acpi = zones.iter().find(|s| s.chip == "acpitz").unwrap();
+        assert_eq!(acpi.value, 27.8);
+        assert_eq!(acpi.critical, Some(119.0));
+        // A passive trip point is not a critical threshold
+        let pkg = zones.iter().find(|s| s.chip == "x86_pkg_temp").unwrap();
+        assert_eq!(pkg.critical, None);
+    }
+
+    #[test]
+    fn prefers_package_sensor_over_acpi_and_drives() {
+        let sensors = read_sensors(&fixture_root());
+        assert_eq!(cpu_package_temperature(&sensors), Some(52.0));
+    }
+
+    #[test]
+    fn prefers_tdie_on_amd_and_ignores_non_cpu_chips() {
+        let amd = vec![
+            temp("acpitz", None, 16.8),
+            temp("k10temp", Some("Tctl"), 71.0),
+            temp("k10temp", Some("Tdie"), 61.0),
+            temp("k10temp", Some("Tccd1"), 58.0),
+        ];
+        assert_eq!(cpu_package_temperature(&amd), Some(61.0));
+
+        let drives_only = vec![temp("nvme", Some("Composite"), 40.0), temp("amdgpu", Some("edge"), 50.0)];
+        assert_eq!(cpu_package_temperature(&drives_only), None);
+    }
+
+    #[test]
+    fn unknown_chips_only_count_when_labelled_as_cpu() {
+        let no_cpu_driver = vec![
+            temp("iwlwifi_1", None, 45.0),
+            temp("spd5118", None, 38.0),
+            temp("dell_smm", Some("Ambient"), 33.0),
+        ];
+        assert_eq!(cpu_package_temperature(&no_cpu_driver), None);
+
+        let labelled = vec![
+            temp("acpitz", None, 27.8),
+            temp("thinkpad", Some("GPU"), 50.0),
+            temp("thinkpad", Some("CPU"), 57.0),
+        ];
+        assert_eq!(cpu_package_temperature(&labelled), Some(57.0));
+    }
+
+    #[test]
+    fn missing_root_yields_no_sensors() {
+        assert!(read_sensors(Path::new("/nonexistent/sysfs")).is_empty());
+    }
+}
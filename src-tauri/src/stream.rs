@@ -0,0 +1,193 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::sampler::SamplerState;
+use crate::{build_network_info, build_system_overview, fetch_gpu_info, NetworkInfo, SystemOverview};
+
+pub const SNAPSHOT_EVENT: &str = "metrics-snapshot";
+
+const MIN_INTERVAL_MS: u64 = 250;
+const IDLE_POLL: Duration = Duration::from_millis(200);
+
+#[derive(Serialize)]
+pub struct MetricsSnapshot {
+    sequence: u64,
+    timestamp_unix_ms: u64,
+    overview: SystemOverview,
+    network: NetworkInfo,
+}
+
+#[derive(Serialize, Clone)]
+pub struct Subscription {
+    pub id: u64,
+    pub target: String,
+    pub interval_ms: u64,
+}
+
+// Registry of windows that want periodic snapshots; one loop serves all of them
+pub struct StreamHub {
+    subscriptions: Vec<Subscription>,
+    next_id: u64,
+    sequence: u64,
+    started: Instant,
+}
+
+pub type StreamState = Arc<Mutex<StreamHub>>;
+
+impl StreamHub {
+    pub fn new() -> Self {
+        StreamHub {
+            subscriptions: Vec::new(),
+            next_id: 1,
+            sequence: 0,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn subscribe(&mut self, target: String, interval_ms: u64) -> Subscription {
+        let subscription = Subscription {
+            id: self.next_id,
+            target,
+            interval_ms: interval_ms.max(MIN_INTERVAL_MS),
+        };
+        self.next_id += 1;
+        self.subscriptions.push(subscription.clone());
+        subscription
+    }
+
+    pub fn unsubscribe(&mut self, id: u64) -> bool {
+        let before = self.subscriptions.len();
+        self.subscriptions.retain(|s| s.id != id);
+        self.subscriptions.len() != before
+    }
+
+    pub fn remove_target(&mut self, target: &str) {
+        self.subscriptions.retain(|s| s.target != target);
+    }
+
+    pub fn subscriptions(&self) -> Vec<Subscription> {
+        self.subscriptions.clone()
+    }
+
+    // Ticks are aligned to the hub's start time, so subscribers sharing an
+    // interval always receive the same snapshot
+    fn due_targets(&self, previous: Duration, now: Duration) -> Vec<String> {
+        let mut targets: Vec<String> = self
+            .subscriptions
+            .iter()
+            .filter(|s| {
+                let interval = s.interval_ms as u128;
+                now.as_millis() / interval != previous.as_millis() / interval
+            })
+            .map(|s| s.target.clone())
+            .collect();
+        targets.sort();
+        targets.dedup();
+        targets
+    }
+}
+
+fn collect_snapshot(sampler_state: &SamplerState, sequence: u64) -> MetricsSnapshot {
+    let gpus = fetch_gpu_info();
+    let sampler = sampler_state.lock().unwrap();
+    let timestamp_unix_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    MetricsSnapshot {
+        sequence,
+        timestamp_unix_ms,
+        overview: build_system_overview(&sampler, gpus),
+        network: build_network_info(&sampler),
+    }
+}
+
+pub fn spawn(app: AppHandle, hub: StreamState, sampler_state: SamplerState) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut previous = hub.lock().unwrap().started.elapsed();
+        loop {
+            thread::sleep(Duration::from_millis(50));
+
+            let (targets, sequence) = {
+                let mut hub = hub.lock().unwrap();
+                if hub.subscriptions.is_empty() {
+                    drop(hub);
+                    thread::sleep(IDLE_POLL);
+                    continue;
+                }
+                let now = hub.started.elapsed();
+                let targets = hub.due_targets(previous, now);
+                previous = now;
+                if targets.is_empty() {
+                    continue;
+                }
+                hub.sequence += 1;
+                (targets, hub.sequence)
+            };
+
+            let snapshot = collect_snapshot(&sampler_state, sequence);
+            for target in targets {
+                let _ = app.emit_to(target.as_str(), SNAPSHOT_EVENT, &snapshot);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn subscribe_clamps_the_interval_and_assigns_fresh_ids() {
+        let mut hub = StreamHub::new();
+        let fast = hub.subscribe("main".to_string(), 10);
+        let slow = hub.subscribe("main".to_string(), 1000);
+        assert_eq!(fast.interval_ms, MIN_INTERVAL_MS);
+        assert_eq!(slow.interval_ms, 1000);
+        assert_ne!(fast.id, slow.id);
+        assert_eq!(hub.subscriptions().len(), 2);
+    }
+
+    #[test]
+    fn unsubscribe_and_remove_target() {
+        let mut hub = StreamHub::new();
+        let first = hub.subscribe("main".to_string(), 1000);
+        hub.subscribe("main".to_string(), 500);
+        let graph = hub.subscribe("graph".to_string(), 1000);
+
+        assert!(hub.unsubscribe(first.id));
+        assert!(!hub.unsubscribe(first.id));
+        hub.remove_target("main");
+        let left: Vec<u64> = hub.subscriptions().iter().map(|s| s.id).collect();
+        assert_eq!(left, vec![graph.id]);
+    }
+
+    #[test]
+    fn due_targets_follow_ticks_from_the_start_time() {
+        let mut hub = StreamHub::new();
+        hub.subscribe("main".to_string(), 1000);
+        hub.subscribe("graph".to_string(), 500);
+
+        assert!(hub.due_targets(ms(0), ms(450)).is_empty());
+        assert_eq!(hub.due_targets(ms(450), ms(550)), vec!["graph"]);
+        // The next poll within the same tick sends nothing again
+        assert!(hub.due_targets(ms(550), ms(600)).is_empty());
+        assert_eq!(hub.due_targets(ms(950), ms(1020)), vec!["graph", "main"]);
+    }
+
+    #[test]
+    fn due_targets_lists_each_window_once() {
+        let mut hub = StreamHub::new();
+        hub.subscribe("main".to_string(), 250);
+        hub.subscribe("main".to_string(), 1000);
+        assert_eq!(hub.due_targets(ms(990), ms(1010)), vec!["main"]);
+    }
+}
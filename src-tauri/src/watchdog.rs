@@ -0,0 +1,439 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::{ProcessExt, System, SystemExt};
+
+use crate::procctl::{self, ControlError, ProcessTarget};
+use crate::proctrack::{CpuNormalization, ProcessTracker};
+use crate::protection::{self, ProtectionPolicy};
+
+// Most recent triggered actions kept for the history view
+const HISTORY_LIMIT: usize = 500;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "metric", rename_all = "snake_case")]
+pub enum Condition {
+    CpuAbove { percent: f32 }, // percent of one core, as in the process table
+    MemoryAbove { bytes: u64 }, // resident set size
+}
+
+impl Condition {
+    // The observed value when the condition holds
+    fn breached(&self, cpu: f32, memory: u64) -> Option<f64> {
+        match *self {
+            Condition::CpuAbove { percent } => (cpu > percent).then_some(cpu as f64),
+            Condition::MemoryAbove { bytes } => (memory > bytes).then_some(memory as f64),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WatchdogAction {
+    Renice { nice: i32 },
+    Suspend,
+    Terminate, // SIGTERM
+    Kill,      // SIGKILL
+}
+
+// "If a process named `process_name` keeps `condition` true for `duration_secs`, do `action`"
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct WatchdogRule {
+    pub name: String,
+    pub process_name: Option<String>, // None matches every process
+    pub condition: Condition,
+    #[serde(default)]
+    pub duration_secs: u64,
+    pub action: WatchdogAction,
+    // Record what would have happened without touching the process
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl WatchdogRule {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Watchdog rules need a name".to_string());
+        }
+        if let WatchdogAction::Renice { nice } = self.action {
+            if !(-20..=19).contains(&nice) {
+                return Err(format!("Rule \"{}\": nice value {} is outside -20..19", self.name, nice));
+            }
+        }
+        match self.condition {
+            Condition::CpuAbove { percent } if !percent.is_finite() || percent <= 0.0 => {
+                return Err(format!("Rule \"{}\": CPU threshold must be a positive percentage", self.name));
+            }
+            Condition::MemoryAbove { bytes: 0 } => {
+                return Err(format!("Rule \"{}\": memory threshold must be above zero", self.name));
+            }
+            _ => {}
+        }
+        // A rule matching every process would stop or kill the whole session
+        // the moment a threshold is mistyped
+        let destructive = !matches!(self.action, WatchdogAction::Renice { .. });
+        if destructive && self.process_name.as_deref().is_none_or(|n| n.trim().is_empty()) {
+            return Err(format!("Rule \"{}\": suspend, terminate and kill rules need a process name", self.name));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ActionRecord {
+    pub timestamp_unix_ms: u64,
+    pub rule: String,
+    pub pid: i32,
+    pub process_name: String,
+    pub action: WatchdogAction,
+    pub observed: f64, // CPU percent or bytes, matching the rule's condition
+    pub dry_run: bool,
+    pub error: Option<ControlError>, // None when the action succeeded (or would have)
+}
+
+// What a rule is evaluated against
+pub struct ProcessSample {
+    pub pid: i32,
+    pub start_time: u64,
+    pub name: String,
+    pub exe: Option<String>,
+    pub cpu: f32,
+    pub memory: u64,
+}
+
+struct Trigger {
+    rule: usize,
+    sample: usize,
+    observed: f64,
+}
+
+struct Breach {
+    since: Instant,
+    fired: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WatchdogStatus {
+    pub rules_path: Option<String>,
+    pub load_error: Option<String>, // rules are not saved while this is set
+}
+
+// On-disk format, an object so fields can be added later
+#[derive(Deserialize, Serialize, Default)]
+struct WatchdogFile {
+    rules: Vec<WatchdogRule>,
+}
+
+pub struct Watchdog {
+    rules: Vec<WatchdogRule>,
+    // (rule index, pid, start time) -> since when the condition has held
+    breaches: HashMap<(usize, i32, u64), Breach>,
+    history: VecDeque<ActionRecord>,
+    path: Option<PathBuf>,
+    load_error: Option<String>,
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Watchdog {
+            rules: Vec::new(),
+            breaches: HashMap::new(),
+            history: VecDeque::new(),
+            path: None,
+            load_error: None,
+        }
+    }
+
+    // Rules are saved back to `path` whenever they change. A missing file
+    // just means no rules have been defined yet; a file that fails to load is
+    // left alone, and saving is refused so it cannot be overwritten.
+    pub fn load(&mut self, path: PathBuf) -> Result<(), String> {
+        let loaded = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str::<WatchdogFile>(&text)
+                .map_err(|e| format!("Invalid watchdog rules in {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(WatchdogFile::default()),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        self.path = Some(path);
+        self.load_error = loaded.as_ref().err().cloned();
+        self.rules = loaded?.rules;
+        self.breaches.clear();
+        Ok(())
+    }
+
+    pub fn status(&self) -> WatchdogStatus {
+        WatchdogStatus {
+            rules_path: self.path.as_ref().map(|p| p.display().to_string()),
+            load_error: self.load_error.clone(),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let file = WatchdogFile {
+            rules: self.rules.clone(),
+        };
+        let text = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn rules(&self) -> &[WatchdogRule] {
+        &self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: Vec<WatchdogRule>) -> Result<(), String> {
+        for rule in &rules {
+            rule.validate()?;
+        }
+        if let Some(e) = &self.load_error {
+            return Err(format!("{}; fix or remove the file before changing rules", e));
+        }
+        self.rules = rules;
+        // Indices may now refer to different rules
+        self.breaches.clear();
+        self.save()
+    }
+
+    pub fn history(&self) -> impl Iterator<Item = &ActionRecord> {
+        self.history.iter()
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    // Each (rule, process) pair fires once per breach: the condition has to
+    // clear before the same process can trigger the rule again
+    fn observe(&mut self, samples: &[ProcessSample], now: Instant) -> Vec<Trigger> {
+        let mut triggers = Vec::new();
+        let mut holding = HashSet::new();
+
+        for (r, rule) in self.rules.iter().enumerate() {
+            for (s, sample) in samples.iter().enumerate() {
+                if let Some(wanted) = &rule.process_name {
+                    if !protection::name_matches(Some(&sample.name), sample.exe.as_deref(), wanted) {
+                        continue;
+                    }
+                }
+                let Some(observed) = rule.condition.breached(sample.cpu, sample.memory) else {
+                    continue;
+                };
+                let key = (r, sample.pid, sample.start_time);
+                holding.insert(key);
+                let breach = self.breaches.entry(key).or_insert(Breach { since: now, fired: false });
+                if !breach.fired && now.duration_since(breach.since) >= Duration::from_secs(rule.duration_secs) {
+                    breach.fired = true;
+                    triggers.push(Trigger { rule: r, sample: s, observed });
+                }
+            }
+        }
+        self.breaches.retain(|key, _| holding.contains(key));
+        triggers
+    }
+
+    // Called from the sampler after every process refresh. Protected processes
+    // are never acted on; the refusal is recorded like any other failure.
+    pub fn evaluate(&mut self, sys: &mut System, tracker: &ProcessTracker, policy: &ProtectionPolicy) {
+        if self.rules.is_empty() {
+            return;
+        }
+        let samples: Vec<ProcessSample> = sys
+            .processes()
+            .iter()
+            .filter_map(|(pid, proc)| {
+                Some(ProcessSample {
+                    pid: pid.to_string().parse().ok()?,
+                    start_time: proc.start_time(),
+                    name: proc.name().to_string(),
+                    exe: Some(proc.exe().to_string_lossy().into_owned()).filter(|exe| !exe.is_empty()),
+                    cpu: tracker.cpu_usage(*pid, CpuNormalization::PerCore),
+                    memory: proc.memory(),
+                })
+            })
+            .collect();
+
+        for trigger in self.observe(&samples, Instant::now()) {
+            let rule = &self.rules[trigger.rule];
+            let sample = &samples[trigger.sample];
+            let target = ProcessTarget::Pid(sample.pid);
+            let result = policy.check(sys, target).and_then(|()| {
+                if rule.dry_run {
+                    return Ok(());
+                }
+                match rule.action {
+                    WatchdogAction::Renice { nice } => procctl::set_nice(target, nice),
+                    WatchdogAction::Suspend => procctl::suspend(target),
+                    WatchdogAction::Terminate => procctl::signal_exit(target, false),
+                    WatchdogAction::Kill => procctl::signal_exit(target, true),
+                }
+            });
+
+            let record = ActionRecord {
+                timestamp_unix_ms: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0),
+                rule: rule.name.clone(),
+                pid: sample.pid,
+                process_name: sample.name.clone(),
+                action: rule.action,
+                observed: trigger.observed,
+                dry_run: rule.dry_run,
+                error: result.err(),
+            };
+            if self.history.len() == HISTORY_LIMIT {
+                self.history.pop_front();
+            }
+            self.history.push_back(record);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(process_name: Option<&str>, condition: Condition, duration_secs: u64) -> WatchdogRule {
+        WatchdogRule {
+            name: "test".to_string(),
+            process_name: process_name.map(str::to_string),
+            condition,
+            duration_secs,
+            action: WatchdogAction::Renice { nice: 10 },
+            dry_run: true,
+        }
+    }
+
+    fn sample(pid: i32, name: &str, cpu: f32, memory: u64) -> ProcessSample {
+        ProcessSample {
+            pid,
+            start_time: 100,
+            name: name.to_string(),
+            exe: None,
+            cpu,
+            memory,
+        }
+    }
+
+    #[test]
+    fn fires_once_after_the_condition_holds_for_the_duration() {
+        let mut watchdog = Watchdog::new();
+        watchdog
+            .set_rules(vec![rule(Some("encoder"), Condition::CpuAbove { percent: 90.0 }, 60)])
+            .unwrap();
+        let start = Instant::now();
+        let busy = [sample(10, "encoder", 95.0, 0), sample(11, "shell", 99.0, 0)];
+
+        assert!(watchdog.observe(&busy, start).is_empty());
+        assert!(watchdog.observe(&busy, start + Duration::from_secs(30)).is_empty());
+        let fired = watchdog.observe(&busy, start + Duration::from_secs(60));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(busy[fired[0].sample].pid, 10);
+        assert_eq!(fired[0].observed, 95.0);
+        // Still breaching, but already acted on
+        assert!(watchdog.observe(&busy, start + Duration::from_secs(120)).is_empty());
+    }
+
+    #[test]
+    fn a_dip_below_the_threshold_restarts_the_clock() {
+        let mut watchdog = Watchdog::new();
+        watchdog
+            .set_rules(vec![rule(None, Condition::MemoryAbove { bytes: 4 << 30 }, 10)])
+            .unwrap();
+        let start = Instant::now();
+        let big = [sample(20, "browser", 0.0, 5 << 30)];
+        let small = [sample(20, "browser", 0.0, 1 << 30)];
+
+        watchdog.observe(&big, start);
+        watchdog.observe(&small, start + Duration::from_secs(5));
+        assert!(watchdog.observe(&big, start + Duration::from_secs(12)).is_empty());
+        assert_eq!(watchdog.observe(&big, start + Duration::from_secs(22)).len(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_rules_and_persists_valid_ones() {
+        let path = std::env::temp_dir().join(format!("sys-dock-watchdog-{}.json", std::process::id()));
+        let mut watchdog = Watchdog::new();
+        watchdog.load(path.clone()).unwrap();
+
+        let mut bad = rule(None, Condition::CpuAbove { percent: 50.0 }, 0);
+        bad.action = WatchdogAction::Renice { nice: 30 };
+        assert!(watchdog.set_rules(vec![bad]).is_err());
+
+        let zero_cpu = rule(Some("make"), Condition::CpuAbove { percent: 0.0 }, 5);
+        assert!(watchdog.set_rules(vec![zero_cpu]).is_err());
+        let zero_memory = rule(Some("make"), Condition::MemoryAbove { bytes: 0 }, 5);
+        assert!(watchdog.set_rules(vec![zero_memory]).is_err());
+        let mut kill_everything = rule(None, Condition::CpuAbove { percent: 50.0 }, 5);
+        kill_everything.action = WatchdogAction::Kill;
+        assert!(watchdog.set_rules(vec![kill_everything.clone()]).is_err());
+        kill_everything.process_name = Some("  ".to_string());
+        assert!(watchdog.set_rules(vec![kill_everything]).is_err());
+
+        let good = rule(Some("make"), Condition::CpuAbove { percent: 50.0 }, 5);
+        watchdog.set_rules(vec![good.clone()]).unwrap();
+        let mut reloaded = Watchdog::new();
+        reloaded.load(path.clone()).unwrap();
+        assert_eq!(reloaded.rules(), &[good]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn a_file_that_fails_to_load_is_never_overwritten() {
+        let path = std::env::temp_dir().join(format!("sys-dock-watchdog-bad-{}.json", std::process::id()));
+        fs::write(&path, "{ \"rules\": [ oops").unwrap();
+        let mut watchdog = Watchdog::new();
+        assert!(watchdog.load(path.clone()).is_err());
+        assert!(watchdog.status().load_error.is_some());
+
+        let good = rule(Some("make"), Condition::CpuAbove { percent: 50.0 }, 5);
+        assert!(watchdog.set_rules(vec![good]).is_err());
+        assert!(watchdog.rules().is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ \"rules\": [ oops");
+
+        fs::write(&path, "{ \"rules\": [] }").unwrap();
+        watchdog.load(path.clone()).unwrap();
+        assert_eq!(watchdog.status().load_error, None);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dry_run_records_without_acting() {
+        // A copy of sleep under a unique name, so the rule cannot match other processes
+        let dir = std::env::temp_dir().join(format!("sys-dock-watchdog-exe-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let unique = format!("wd-sleep-{}", std::process::id());
+        let exe = dir.join(&unique);
+        fs::copy("/bin/sleep", &exe).unwrap();
+        let mut child = std::process::Command::new(&exe).arg("30").spawn().unwrap();
+        let pid = child.id() as i32;
+        let mut sys = System::new();
+        sys.refresh_processes();
+        let mut tracker = ProcessTracker::new();
+        tracker.update(&sys);
+
+        let mut watchdog = Watchdog::new();
+        let mut kill = rule(Some(&unique), Condition::MemoryAbove { bytes: 1 }, 0);
+        kill.action = WatchdogAction::Kill;
+        watchdog.set_rules(vec![kill]).unwrap();
+        watchdog.evaluate(&mut sys, &tracker, &ProtectionPolicy::with_rules(Vec::new()));
+
+        let records: Vec<&ActionRecord> = watchdog.history().collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].pid, pid);
+        assert!(records[0].dry_run);
+        assert!(records[0].error.is_none());
+        assert!(child.try_wait().unwrap().is_none());
+        child.kill().unwrap();
+        child.wait().unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
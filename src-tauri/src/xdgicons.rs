@@ -0,0 +1,483 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// The parts of a .desktop file that tie an application to its icon
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DesktopEntry {
+    pub id: String, // file name without ".desktop"
+    pub exec: Option<String>,
+    pub try_exec: Option<String>,
+    pub wm_class: Option<String>,
+    pub icon: Option<String>,
+}
+
+// Only the [Desktop Entry] group counts; actions and localized keys are skipped.
+// Hidden entries are deleted ones and yield None.
+pub fn parse_desktop_entry(id: &str, text: &str) -> Option<DesktopEntry> {
+    let mut entry = DesktopEntry {
+        id: id.to_string(),
+        ..Default::default()
+    };
+    let mut in_main_group = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main_group = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_main_group || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "Type" if value != "Application" => return None,
+            "Hidden" if value == "true" => return None,
+            "Exec" => entry.exec = Some(value),
+            "TryExec" => entry.try_exec = Some(value),
+            "StartupWMClass" => entry.wm_class = Some(value),
+            "Icon" => entry.icon = Some(value),
+            _ => {}
+        }
+    }
+    entry.icon.is_some().then_some(entry)
+}
+
+// The program an Exec line runs: the first word that is not `env` or a VAR=value
+fn exec_program(exec: &str) -> Option<&str> {
+    exec.split_whitespace()
+        .map(|word| word.trim_matches('"'))
+        .find(|word| *word != "env" && !word.contains('='))
+}
+
+// Desktop files under <data dir>/applications. Subdirectories prefix the ID
+// ("kde4/okular.desktop" is "kde4-okular"), and earlier data dirs win.
+pub fn load_desktop_entries(data_dirs: &[PathBuf]) -> Vec<DesktopEntry> {
+    fn walk(dir: &Path, prefix: &str, seen: &mut HashSet<String>, out: &mut Vec<DesktopEntry>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        paths.sort();
+        for path in paths {
+            let Some(file) = path.file_name().and_then(|f| f.to_str()) else {
+                continue;
+            };
+            if path.is_dir() {
+                walk(&path, &format!("{}{}-", prefix, file), seen, out);
+            } else if let Some(stem) = file.strip_suffix(".desktop") {
+                let id = format!("{}{}", prefix, stem);
+                if seen.insert(id.clone()) {
+                    if let Some(entry) = fs::read_to_string(&path).ok().and_then(|t| parse_desktop_entry(&id, &t)) {
+                        out.push(entry);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for dir in data_dirs {
+        walk(&dir.join("applications"), "", &mut seen, &mut out);
+    }
+    out
+}
+
+// Best desktop entry for an executable: an Exec/TryExec naming this exact path,
+// then one naming the same file name, then a matching StartupWMClass or desktop
+// ID ("org.gnome.Nautilus" for nautilus)
+pub fn match_entry<'a>(entries: &'a [DesktopEntry], exe: &Path) -> Option<&'a DesktopEntry> {
+    let file_name = exe.file_name()?.to_str()?;
+    let score = |entry: &DesktopEntry| -> u8 {
+        let programs = [entry.exec.as_deref().and_then(exec_program), entry.try_exec.as_deref()];
+        let programs = programs.iter().flatten().map(Path::new);
+        let mut best = 0;
+        for program in programs {
+            if program.is_absolute() && program == exe {
+                return 4;
+            }
+            if program.file_name().and_then(|f| f.to_str()) == Some(file_name) {
+                best = 3;
+            }
+        }
+        let last_id_part = entry.id.rsplit('.').next().unwrap_or(&entry.id);
+        if best == 0 && entry.wm_class.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(file_name)) {
+            best = 2;
+        }
+        if best == 0 && (entry.id.eq_ignore_ascii_case(file_name) || last_id_part.eq_ignore_ascii_case(file_name)) {
+            best = 1;
+        }
+        best
+    };
+    entries
+        .iter()
+        .map(|entry| (score(entry), entry))
+        .filter(|(score, _)| *score > 0)
+        // max_by_key keeps the last maximum; reverse so earlier entries win ties
+        .rev()
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, entry)| entry)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DirKind {
+    Fixed,
+    Scalable { min: u32, max: u32 },
+    Threshold { threshold: u32 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ThemeDir {
+    path: String,
+    size: u32,
+    kind: DirKind,
+}
+
+impl ThemeDir {
+    // DirectorySizeDistance from the icon theme spec (scale 1)
+    fn distance(&self, size: u32) -> u32 {
+        match self.kind {
+            DirKind::Fixed => self.size.abs_diff(size),
+            DirKind::Scalable { min, max } => min.saturating_sub(size) + size.saturating_sub(max),
+            DirKind::Threshold { threshold } => {
+                self.size.saturating_sub(threshold).saturating_sub(size) + size.saturating_sub(self.size + threshold)
+            }
+        }
+    }
+}
+
+// index.theme: the directory list with each directory's size rules, and the
+// themes to fall back to
+fn parse_index_theme(text: &str) -> (Vec<ThemeDir>, Vec<String>) {
+    let mut groups: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = String::new();
+    for line in text.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = name.to_string();
+        } else if let Some((key, value)) = line.split_once('=') {
+            groups
+                .entry(current.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    let list = |value: Option<&String>| -> Vec<String> {
+        value
+            .map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+    let header = groups.get("Icon Theme");
+    let inherits = list(header.and_then(|h| h.get("Inherits")));
+
+    let dirs = list(header.and_then(|h| h.get("Directories")))
+        .into_iter()
+        .filter_map(|path| {
+            let group = groups.get(&path)?;
+            let num = |key: &str| group.get(key).and_then(|v| v.parse::<u32>().ok());
+            let size = num("Size")?;
+            // Icons drawn for HiDPI are twice the nominal size; leave them out
+            if num("Scale").unwrap_or(1) != 1 {
+                return None;
+            }
+            let kind = match group.get("Type").map(String::as_str) {
+                Some("Fixed") => DirKind::Fixed,
+                Some("Scalable") => DirKind::Scalable {
+                    min: num("MinSize").unwrap_or(size),
+                    max: num("MaxSize").unwrap_or(size),
+                },
+                _ => DirKind::Threshold {
+                    threshold: num("Threshold").unwrap_or(2),
+                },
+            };
+            Some(ThemeDir { path, size, kind })
+        })
+        .collect();
+    (dirs, inherits)
+}
+
+const ICON_EXTENSIONS: [&str; 2] = ["png", "svg"];
+
+// One theme with its directories listed up front, so lookups are map hits
+// rather than a stat per directory per base dir
+struct IconTheme {
+    dirs: Vec<ThemeDir>,
+    // icon name -> (index into dirs, file)
+    files: HashMap<String, Vec<(usize, PathBuf)>>,
+}
+
+impl IconTheme {
+    fn load(name: &str, base_dirs: &[PathBuf]) -> Option<(IconTheme, Vec<String>)> {
+        let roots: Vec<PathBuf> = base_dirs.iter().map(|b| b.join(name)).filter(|r| r.is_dir()).collect();
+        let index = roots.iter().find_map(|r| fs::read_to_string(r.join("index.theme")).ok())?;
+        let (dirs, inherits) = parse_index_theme(&index);
+
+        let mut files: HashMap<String, Vec<(usize, PathBuf)>> = HashMap::new();
+        for (i, dir) in dirs.iter().enumerate() {
+            for root in &roots {
+                let Ok(entries) = fs::read_dir(root.join(&dir.path)) else {
+                    continue;
+                };
+                for path in entries.flatten().map(|e| e.path()) {
+                    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                    if !ICON_EXTENSIONS.contains(&ext) {
+                        continue;
+                    }
+                    if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                        files.entry(stem.to_string()).or_default().push((i, path.clone()));
+                    }
+                }
+            }
+        }
+        Some((IconTheme { dirs, files }, inherits))
+    }
+
+    // The file whose directory is closest to `size`, preferring earlier directories
+    fn lookup(&self, icon: &str, size: u32) -> Option<&Path> {
+        self.files
+            .get(icon)?
+            .iter()
+            .min_by_key(|(dir, _)| (self.dirs[*dir].distance(size), *dir))
+            .map(|(_, path)| path.as_path())
+    }
+}
+
+pub struct IconResolver {
+    themes: Vec<IconTheme>, // the chosen theme, what it inherits, then hicolor
+    fallback_dirs: Vec<PathBuf>,
+}
+
+impl IconResolver {
+    // `base_dirs` are the icon search path ($HOME/.icons, $XDG_DATA_DIRS/icons);
+    // `fallback_dirs` hold unthemed icons such as /usr/share/pixmaps
+    pub fn new(base_dirs: &[PathBuf], fallback_dirs: Vec<PathBuf>, theme: Option<&str>) -> Self {
+        let mut themes = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue: Vec<String> = theme.into_iter().map(str::to_string).collect();
+        while !queue.is_empty() {
+            let name = queue.remove(0);
+            if name == "hicolor" || !visited.insert(name.clone()) {
+                continue;
+            }
+            if let Some((loaded, inherits)) = IconTheme::load(&name, base_dirs) {
+                themes.push(loaded);
+                queue.extend(inherits);
+            }
+        }
+        // Every theme implicitly ends in hicolor, where applications install their icons
+        if let Some((hicolor, _)) = IconTheme::load("hicolor", base_dirs) {
+            themes.push(hicolor);
+        }
+        IconResolver { themes, fallback_dirs }
+    }
+
+    pub fn find_icon(&self, icon: &str, size: u32) -> Option<PathBuf> {
+        // Icon= may be an absolute path instead of a name
+        if Path::new(icon).is_absolute() {
+            return Path::new(icon).is_file().then(|| PathBuf::from(icon));
+        }
+        self.themes
+            .iter()
+            .find_map(|theme| theme.lookup(icon, size).map(Path::to_path_buf))
+            .or_else(|| {
+                self.fallback_dirs.iter().find_map(|dir| {
+                    ICON_EXTENSIONS
+                        .iter()
+                        .map(|ext| dir.join(format!("{}.{}", icon, ext)))
+                        .find(|path| path.is_file())
+                })
+            })
+    }
+}
+
+// PNG of `size`x`size` pixels; SVGs are rasterized, bitmaps scaled to fit
+pub fn render_png(path: &Path, size: u32) -> Option<Vec<u8>> {
+    let data = fs::read(path).ok()?;
+    let is_svg = matches!(path.extension().and_then(|e| e.to_str()), Some("svg") | Some("svgz"));
+    if is_svg {
+        use resvg::{tiny_skia, usvg};
+        let tree = usvg::Tree::from_data(&data, &usvg::Options::default()).ok()?;
+        let mut pixmap = tiny_skia::Pixmap::new(size, size)?;
+        let view = tree.size();
+        let scale = size as f32 / view.width().max(view.height());
+        let transform = tiny_skia::Transform::from_scale(scale, scale).post_translate(
+            (size as f32 - view.width() * scale) / 2.0,
+            (size as f32 - view.height() * scale) / 2.0,
+        );
+        resvg::render(&tree, transform, &mut pixmap.as_mut());
+        return pixmap.encode_png().ok();
+    }
+
+    use image::imageops::{self, FilterType};
+    use image::{DynamicImage, ImageOutputFormat, RgbaImage};
+    let img = image::load_from_memory(&data).ok()?;
+    let img = if img.width() == size && img.height() == size {
+        img
+    } else {
+        // resize keeps the aspect ratio, so pad non-square icons like the SVG branch
+        let scaled = img.resize(size, size, FilterType::Lanczos3).to_rgba8();
+        let mut canvas = RgbaImage::new(size, size);
+        let x = (size - scaled.width()) / 2;
+        let y = (size - scaled.height()) / 2;
+        imageops::overlay(&mut canvas, &scaled, x as i64, y as i64);
+        DynamicImage::ImageRgba8(canvas)
+    };
+    let mut buf = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut buf, ImageOutputFormat::Png).ok()?;
+    Some(buf.into_inner())
+}
+
+// Desktop entries plus the icon theme, loaded once
+pub struct IconLookup {
+    entries: Vec<DesktopEntry>,
+    resolver: IconResolver,
+}
+
+impl IconLookup {
+    pub fn new(data_dirs: &[PathBuf], extra_icon_dirs: &[PathBuf], theme: Option<&str>) -> Self {
+        let base_dirs: Vec<PathBuf> = extra_icon_dirs
+            .iter()
+            .cloned()
+            .chain(data_dirs.iter().map(|d| d.join("icons")))
+            .collect();
+        let pixmaps = data_dirs.iter().map(|d| d.join("pixmaps")).collect();
+        IconLookup {
+            entries: load_desktop_entries(data_dirs),
+            resolver: IconResolver::new(&base_dirs, pixmaps, theme),
+        }
+    }
+
+    // XDG base directories and the GTK icon theme of the current user
+    pub fn from_environment() -> Self {
+        let home = env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        let data_home = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".local/share"));
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".config"));
+        let data_dirs_var = env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+        let mut data_dirs = vec![data_home];
+        data_dirs.extend(data_dirs_var.split(':').filter(|d| !d.is_empty()).map(PathBuf::from));
+
+        let theme = ["gtk-3.0/settings.ini", "gtk-4.0/settings.ini"].iter().find_map(|file| {
+            let text = fs::read_to_string(config_home.join(file)).ok()?;
+            text.lines().find_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                (key.trim() == "gtk-icon-theme-name").then(|| value.trim().trim_matches('"').to_string())
+            })
+        });
+        IconLookup::new(&data_dirs, &[home.join(".icons")], theme.as_deref())
+    }
+
+    pub fn icon_path(&self, exe: &Path, size: u32) -> Option<PathBuf> {
+        let entry = match_entry(&self.entries, exe)?;
+        self.resolver.find_icon(entry.icon.as_deref()?, size)
+    }
+
+    pub fn icon_png(&self, exe: &Path, size: u32) -> Option<Vec<u8>> {
+        render_png(&self.icon_path(exe, size)?, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/xdg/share")
+    }
+
+    fn lookup(theme: Option<&str>) -> IconLookup {
+        IconLookup::new(&[share()], &[], theme)
+    }
+
+    #[test]
+    fn parses_desktop_entries() {
+        let entries = load_desktop_entries(&[share()]);
+        let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        // hidden.desktop is deleted, the link has no icon
+        assert_eq!(ids, vec!["code", "legacy", "org.gnome.Nautilus", "org.mozilla.firefox", "tools-editor"]);
+
+        let firefox = &entries[3];
+        assert_eq!(firefox.exec.as_deref(), Some("env MOZ_ENABLE_WAYLAND=1 firefox %u"));
+        assert_eq!(firefox.icon.as_deref(), Some("firefox"));
+        assert_eq!(exec_program(firefox.exec.as_deref().unwrap()), Some("firefox"));
+    }
+
+    #[test]
+    fn matches_processes_to_entries() {
+        let entries = load_desktop_entries(&[share()]);
+        let id = |exe: &str| match_entry(&entries, Path::new(exe)).map(|e| e.id.as_str());
+        // Exec names the launcher; the running binary lives elsewhere
+        assert_eq!(id("/usr/lib/firefox/firefox"), Some("org.mozilla.firefox"));
+        assert_eq!(id("/usr/share/code/code"), Some("code"));
+        // StartupWMClass, then desktop ID
+        assert_eq!(id("/opt/editor/Editor"), Some("tools-editor"));
+        assert_eq!(id("/usr/bin/nautilus"), Some("org.gnome.Nautilus"));
+        assert_eq!(id("/usr/bin/bash"), None);
+    }
+
+    #[test]
+    fn resolves_icons_through_the_theme_chain() {
+        let hicolor = lookup(None);
+        let name = |exe: &str, l: &IconLookup| {
+            l.icon_path(Path::new(exe), 32)
+                .map(|p| p.strip_prefix(share()).unwrap().to_string_lossy().into_owned())
+        };
+        // 24x24 is closer to 32 than 48x48
+        assert_eq!(name("/usr/lib/firefox/firefox", &hicolor).as_deref(), Some("icons/hicolor/24x24/apps/firefox.png"));
+        assert_eq!(name("/usr/share/code/code", &hicolor).as_deref(), Some("icons/hicolor/scalable/apps/vscode.svg"));
+        assert_eq!(name("/usr/bin/nautilus", &hicolor).as_deref(), Some("icons/hicolor/48x48/apps/org.gnome.Nautilus.png"));
+        assert_eq!(name("/usr/bin/legacy", &hicolor).as_deref(), Some("pixmaps/legacy.png"));
+
+        // A theme's own icons win; anything it lacks comes from what it inherits
+        let themed = lookup(Some("Custom"));
+        assert_eq!(name("/usr/bin/nautilus", &themed).as_deref(), Some("icons/Custom/32x32/apps/org.gnome.Nautilus.png"));
+        assert_eq!(name("/usr/lib/firefox/firefox", &themed).as_deref(), Some("icons/hicolor/24x24/apps/firefox.png"));
+    }
+
+    #[test]
+    fn renders_png_and_svg_at_the_requested_size() {
+        let l = lookup(None);
+        for exe in ["/usr/lib/firefox/firefox", "/usr/share/code/code"] {
+            let png = l.icon_png(Path::new(exe), 32).unwrap();
+            let img = image::load_from_memory(&png).unwrap();
+            assert_eq!((img.width(), img.height()), (32, 32), "{}", exe);
+        }
+    }
+
+    #[test]
+    fn centers_non_square_bitmaps() {
+        let path = std::env::temp_dir().join(format!("sys-dock-wide-{}.png", std::process::id()));
+        image::RgbaImage::from_pixel(48, 24, image::Rgba([255, 0, 0, 255])).save(&path).unwrap();
+        let png = render_png(&path, 32).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let img = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(img.dimensions(), (32, 32));
+        // 32x16 of content with 8 transparent rows above and below
+        assert_eq!(img.get_pixel(16, 3)[3], 0);
+        assert_eq!(img.get_pixel(16, 16)[3], 255);
+        assert_eq!(img.get_pixel(16, 28)[3], 0);
+    }
+
+    #[test]
+    fn threshold_directories_match_nearby_sizes() {
+        let (dirs, inherits) = parse_index_theme(
+            "[Icon Theme]\nDirectories=32x32/apps,scalable/apps\nInherits=Adwaita, hicolor\n\n\
+             [32x32/apps]\nSize=32\n\n[scalable/apps]\nSize=64\nType=Scalable\nMinSize=8\nMaxSize=512\n",
+        );
+        assert_eq!(inherits, vec!["Adwaita", "hicolor"]);
+        assert_eq!(dirs[0].distance(30), 0);
+        assert_eq!(dirs[0].distance(48), 14);
+        assert_eq!(dirs[1].distance(1024), 512);
+    }
+}
@@ -6,7 +6,6 @@ import PerformanceChart from './widgets/PerformanceChart';
 import ReminderWidget from './widgets/ReminderWidget';
 import './Overview.css';
 import { useAlert } from '../context/AlertContext';
-import { getSimulatedGpuUsage } from './gpuSim';
 import { CpuIcon, MemoryIcon, ProcessesIcon, UptimeIcon, StorageIcon, HealthIcon } from '../assets/icons/OverviewIcons';
 
 interface DiskInfo {
@@ -32,6 +31,7 @@ interface SystemOverview {
     uptime?: number;
   };
   disks: DiskInfo[];
+  gpus?: { name: string; utilization?: number | null }[];
 }
 
 const Overview: React.FC = () => {
@@ -40,7 +40,6 @@ const Overview: React.FC = () => {
   const [cpuHistory, setCpuHistory] = useState<number[]>(Array(16).fill(0));
   const [error, setError] = useState<string | null>(null);
   const { setAlert } = useAlert();
-  const [gpuTick, setGpuTick] = useState(() => Number(localStorage.getItem('gpuTick')) || 0);
   const [gpu0Data, setGpu0Data] = useState<number[]>(Array(16).fill(0));
   const [currentTime, setCurrentTime] = useState(new Date());
   const [username, setUsername] = useState<string>('');
@@ -120,6 +119,7 @@ const Overview: React.FC = () => {
       try {
         const data = await invoke<SystemOverview>('fetch_system_overview');
         setCpuHistory(prev => [...prev.slice(1), data.cpu.usage]);
+        setGpu0Data(prev => [...prev.slice(1), data.gpus?.[0]?.utilization ?? 0]);
         setSystemData(data);
         setError(null);
       } catch (err: any) {
@@ -153,18 +153,6 @@ const Overview: React.FC = () => {
     return systemData.cpu.name;
   };
 
-  useEffect(() => {
-    const interval = setInterval(() => {
-      setGpuTick(t => {
-        const nextTick = t + 1;
-        localStorage.setItem('gpuTick', String(nextTick));
-        return nextTick;
-      });
-      setGpu0Data(prev => [...prev.slice(1), getSimulatedGpuUsage(gpuTick, 0)]);
-    }, 2000);
-    return () => clearInterval(interval);
-  }, [gpuTick]);
-
   useEffect(() => {
     const timer = setInterval(() => setCurrentTime(new Date()), 1000);
     return () => clearInterval(timer);
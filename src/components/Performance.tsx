@@ -1,9 +1,8 @@
-import React, { useState, useEffect, useRef, useMemo, useCallback } from 'react';
+import React, { useState, useEffect, useMemo, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { useLocation } from 'react-router-dom'; // Add this import
 import { useAlert } from '../context/AlertContext';
 import './Performance.css';
-import { getSimulatedGpuUsage } from './gpuSim';
 import Sidebar from './Sidebar'; // Add this import
 
 function ConfirmDialog({ open, onConfirm, onCancel }: { open: boolean, onConfirm: () => void, onCancel: () => void }) {
@@ -69,6 +68,7 @@ const Performance: React.FC = () => {
     l1_cache?: number;
     l2_cache?: number;
     l3_cache?: number;
+    per_core?: { index: number; usage?: number | null; frequency?: number | null; online: boolean }[];
   }>({});
   const [memory, setMemory] = useState<{ total?: number; used?: number; available?: number; percentage?: number }>({});
   const [disks, setDisks] = useState<any[]>([]);
@@ -91,9 +91,8 @@ const Performance: React.FC = () => {
   const [speedTestResult, setSpeedTestResult] = useState<{ download_mbps: number; upload_mbps: number; latency_ms: number } | null>(null);
   const [showSpeedTestModal, setShowSpeedTestModal] = useState(false);
 
-  // network rate history and previous snapshot for diffing
+  // network rate history, seeded from and fed by the backend's rate tracker
   const [netHistory, setNetHistory] = useState<{ [ifName: string]: { rx: number[]; tx: number[] } }>({});
-  const netPrevRef = useRef<{ [ifName: string]: { rx: number; tx: number; ts: number } }>({});
 
   useEffect(() => {
     let tick = gpuTick;
@@ -123,21 +122,16 @@ const Performance: React.FC = () => {
         }
         
         if (activeTab === 'gpu') {
-          const fakeGpuUsage = getSimulatedGpuUsage(tick, 0);
-          const fakeGpuTemp = 40 + 25 * Math.abs(Math.sin(tick / 14));
-          const fakeVramUsage = 1024 * 1024 * (2048 + 2048 * Math.abs(Math.sin(tick / 12)));
+          const gpuInfo = result.gpus?.[0] ?? {};
+          const gpuUsage = gpuInfo.utilization ?? 0;
           setGpu({
-            ...result.gpus?.[0],
-            ram: 8192 * 1024 * 1024,
-            driver_version: "AMD 25.8.1",
-            vram_usage: fakeVramUsage,
-            usage: fakeGpuUsage,
-            temperature: fakeGpuTemp,
+            ...gpuInfo,
+            usage: gpuUsage,
           });
           setGpuHistory(h =>
             h.length === 0
-              ? Array(60).fill(fakeGpuUsage)
-              : [...h.slice(-59), fakeGpuUsage]
+              ? Array(60).fill(gpuUsage)
+              : [...h.slice(-59), gpuUsage]
           );
         }
         
@@ -166,12 +160,18 @@ const Performance: React.FC = () => {
             });
             return updated;
           });
-          // Simulate disk I/O rates (in MB/s)
-          const fakeReadSpeed = 200 + 150 * Math.abs(Math.sin(tick / 8));
-          const fakeWriteSpeed = 120 + 100 * Math.abs(Math.sin(tick / 10));
+          // Disk I/O rates (in MB/s) summed over mounted devices. A device mounted
+          // several times (btrfs subvolumes, bind mounts) is counted once.
+          const ioByDevice = new Map<string, any>();
+          result.disks.forEach((disk: any) => {
+            if (disk.io) ioByDevice.set(disk.io.device, disk.io);
+          });
+          const deviceIo = Array.from(ioByDevice.values());
+          const readSpeed = deviceIo.reduce((sum: number, io: any) => sum + io.read_bytes_per_sec, 0) / (1024 * 1024);
+          const writeSpeed = deviceIo.reduce((sum: number, io: any) => sum + io.write_bytes_per_sec, 0) / (1024 * 1024);
           setDiskIOHistory(prev => ({
-            read: [...prev.read.slice(-59), fakeReadSpeed],
-            write: [...prev.write.slice(-59), fakeWriteSpeed]
+            read: [...prev.read.slice(-59), readSpeed],
+            write: [...prev.write.slice(-59), writeSpeed]
           }));
         }
         
@@ -194,35 +194,44 @@ const Performance: React.FC = () => {
     const fetchNetworkRates = async () => {
       try {
         const result = await invoke<any>('fetch_network_info');
-        const now = Date.now();
-        const updatedHistory = { ...netHistory };
-        (result.interfaces ?? []).forEach((iface: any) => {
-          const key = iface.name ?? `iface-${Math.random()}`;
-          const prev = netPrevRef.current[key];
-          let rxRate = 0;
-          let txRate = 0;
-          
-          if (prev) {
-            const dt = Math.max(0.5, (now - prev.ts) / 1000.0); // Allow faster updates
-            const rxDiff = (iface.bytes_received ?? 0) - prev.rx;
-            const txDiff = (iface.bytes_transmitted ?? 0) - prev.tx;
-            rxRate = Math.max(0, rxDiff / dt);
-            txRate = Math.max(0, txDiff / dt);
-          }
-          if (!updatedHistory[key]) updatedHistory[key] = { rx: Array(60).fill(0), tx: Array(60).fill(0) };
-          updatedHistory[key].rx = [...(updatedHistory[key].rx.slice(-59)), rxRate];
-          updatedHistory[key].tx = [...(updatedHistory[key].tx.slice(-59)), txRate];
-          // Always update previous snapshot for next calculation
-          netPrevRef.current[key] = { rx: iface.bytes_received ?? 0, tx: iface.bytes_transmitted ?? 0, ts: now };
+        setNetHistory(prev => {
+          const updatedHistory = { ...prev };
+          (result.interfaces ?? []).forEach((iface: any, i: number) => {
+            const key = iface.name ?? `iface-${i}`;
+            const hist = updatedHistory[key] ?? { rx: Array(60).fill(0), tx: Array(60).fill(0) };
+            updatedHistory[key] = {
+              rx: [...hist.rx.slice(-59), iface.rate?.rx_bytes_per_sec ?? 0],
+              tx: [...hist.tx.slice(-59), iface.rate?.tx_bytes_per_sec ?? 0],
+            };
+          });
+          return updatedHistory;
         });
-        setNetHistory(updatedHistory);
         setNetworkInfo(result);
       } catch (e) {
         setNetworkInfo({ interfaces: [] });
       }
     };
 
-    fetchNetworkRates();
+    // Fill the charts with the last minute the backend already recorded
+    const loadNetworkHistory = async () => {
+      try {
+        const history = await invoke<any[]>('fetch_network_history', { seconds: 60 });
+        const seeded: { [ifName: string]: { rx: number[]; tx: number[] } } = {};
+        history.forEach((h: any) => {
+          const rx = h.samples.map((s: any) => s.rx_bytes_per_sec);
+          const tx = h.samples.map((s: any) => s.tx_bytes_per_sec);
+          seeded[h.interface] = {
+            rx: [...Array(Math.max(0, 60 - rx.length)).fill(0), ...rx].slice(-60),
+            tx: [...Array(Math.max(0, 60 - tx.length)).fill(0), ...tx].slice(-60),
+          };
+        });
+        setNetHistory(seeded);
+      } catch (e) {
+        // keep whatever history is already shown
+      }
+    };
+
+    loadNetworkHistory().then(fetchNetworkRates);
     const interval = setInterval(fetchNetworkRates, 1000); // Poll every 1 second for real-time updates
     return () => clearInterval(interval);
   }, [activeTab]); // eslint-disable-line react-hooks/exhaustive-deps
@@ -524,14 +533,13 @@ const Performance: React.FC = () => {
             <div className="cpu-cores-card">
               <h3 className="cpu-cores-title">Per-Core Usage</h3>
               <div className="cpu-cores-grid">
-                {Array.from({ length: cpu.cores ?? 0 }).map((_, i) => {
-                  // Simulate per-core usage based on overall usage with some variation
-                  const coreUsage = Math.min(100, Math.max(0, 
-                    (cpu.usage ?? 0) + (Math.sin(i * 0.5 + gpuTick * 0.1) * 15)
-                  ));
+                {(cpu.per_core ?? []).map((core) => {
+                  const coreUsage = core.online ? Math.min(100, Math.max(0, core.usage ?? 0)) : 0;
                   return (
-                    <div key={i} className="cpu-core-bar-container">
-                      <div className="cpu-core-label">Core {i}</div>
+                    <div key={core.index} className="cpu-core-bar-container">
+                      <div className="cpu-core-label">
+                        Core {core.index}{!core.online && ' (offline)'}
+                      </div>
                       <div className="cpu-core-bar-outer">
                         <div 
                           className="cpu-core-bar-fill"
@@ -1320,7 +1328,7 @@ const Performance: React.FC = () => {
                     </div>
                     <div className="cpu-stat-content">
                       <div className="cpu-stat-label">Total VRAM</div>
-                      <div className="cpu-stat-value">8192 MB</div>
+                      <div className="cpu-stat-value">{gpu.adapter_ram ? `${gpu.adapter_ram} MB` : 'N/A'}</div>
                     </div>
                   </div>
 
@@ -1445,7 +1453,7 @@ const Performance: React.FC = () => {
                   <h3 className="cpu-load-pie-title">VRAM Distribution</h3>
                   <MemoryUsagePieChart 
                     used={gpu.vram_usage ?? 0} 
-                    total={8192 * 1024 * 1024} 
+                    total={(gpu.adapter_ram ?? 0) * 1024 * 1024} 
                   />
                 </div>
 
@@ -1463,7 +1471,7 @@ const Performance: React.FC = () => {
                     </div>
                     <div className="memory-detail-row">
                       <span className="memory-detail-label">Total VRAM</span>
-                      <span className="memory-detail-value">8192 MB</span>
+                      <span className="memory-detail-value">{gpu.adapter_ram ? `${gpu.adapter_ram} MB` : 'N/A'}</span>
                     </div>
                     <div className="memory-detail-row">
                       <span className="memory-detail-label">VRAM Usage</span>
@@ -1,18 +1,86 @@
-import React, { useEffect, useState, useMemo } from "react";
+import React, { useEffect, useRef, useState, useMemo } from "react";
 import { invoke } from '@tauri-apps/api/core';
 import "./Processes.css";
 
 type ProcessInfo = {
   name: string;
   cpu: number;      // %
-  memory: number;   // KB
+  rss_bytes: number;
+  pss_bytes?: number | null;    // Linux only; null where smaps_rollup is unreadable
+  uss_bytes?: number | null;
+  shared_bytes?: number | null;
+  swap_bytes?: number | null;
   pid: number;
+  ppid?: number | null;
+  uid?: string | null;
+  user?: string | null;
+  state?: string;   // "running", "sleeping", "zombie", ...
+  threads?: number | null;
+  start_time?: number; // unix seconds
   exe?: string;
-  icon?: string | null;
+  icon?: string | null; // changes with the executable; dedupes fetch_process_icon calls
   runtime?: number; // seconds
+  net_rx_bytes_per_sec?: number | null; // TCP only; null when unavailable
+  net_tx_bytes_per_sec?: number | null;
+  disk_read_bytes_per_sec?: number | null; // null until sampled twice
+  disk_write_bytes_per_sec?: number | null;
+  cgroup?: string | null;       // Linux only
+  unit?: string | null;         // systemd unit, e.g. "sshd.service"
+  slice?: string | null;
+  container_id?: string | null;
+};
+
+type ProcessDetails = {
+  pid: number;
+  name: string;
+  cmdline: string[];
+  environment?: { [key: string]: string } | null;
+  cwd?: string | null;
+  exe?: string | null;
+  fds?: { fd: number; target: string; kind: string }[] | null;
+  maps?: { regions: number; total_kb: number; file_backed_kb: number; anonymous_kb: number; heap_kb: number; stack_kb: number; mapped_files: string[] } | null;
+  threads: { tid: number; name: string; state: string; cpu: number; cpu_time_secs: number }[];
+  cgroups: { hierarchy: number; controllers: string[]; path: string }[];
+  namespaces: { kind: string; inode: number }[];
+  limits: { name: string; soft?: number | null; hard?: number | null; units?: string | null }[];
+  io?: { read_bytes: number; write_bytes: number; rchar: number; wchar: number } | null;
+};
+
+type TerminationResult = {
+  pid: number;
+  outcome: "terminated" | "killed" | "permission_denied" | "not_found" | "still_running";
+  elapsed_ms: number;
+};
+
+const describeOutcome = (outcome: TerminationResult["outcome"]): string => {
+  switch (outcome) {
+    case "terminated": return "ended";
+    case "killed": return "force-killed after not responding";
+    case "permission_denied": return "permission denied";
+    case "not_found": return "already exited";
+    case "still_running": return "still running (did not respond to kill)";
+  }
 };
 
-type SortColumn = "name" | "cpu" | "memory";
+// Process control commands reject with { kind, message }, e.g. kind "protected"
+type ControlError = { kind: string; message: string };
+
+const errorMessage = (err: unknown): string =>
+  (err as ControlError)?.message ?? String(err);
+
+const toMB = (bytes: number): number => bytes / 1024 / 1024;
+
+// PSS splits shared pages between the processes mapping them, so it can be summed
+// across a multi-process app; RSS would count shared libraries once per process
+const attributedMemory = (p: ProcessInfo): number => p.pss_bytes ?? p.rss_bytes;
+
+const describeMemory = (p: ProcessInfo): string =>
+  ([["RSS", p.rss_bytes], ["PSS", p.pss_bytes], ["USS", p.uss_bytes], ["Shared", p.shared_bytes], ["Swap", p.swap_bytes]] as const)
+    .filter(([, bytes]) => bytes != null)
+    .map(([label, bytes]) => `${label}: ${toMB(bytes as number).toFixed(1)} MB`)
+    .join("\n");
+
+type SortColumn = "name" | "cpu" | "memory" | "network" | "disk";
 type SortDirection = "asc" | "desc";
 
 // Helper function to format runtime
@@ -31,6 +99,25 @@ const formatRuntime = (seconds?: number): string => {
   }
 };
 
+// Combined rx + tx rate; undefined when the backend cannot attribute traffic
+const netRate = (proc: ProcessInfo): number | undefined =>
+  proc.net_rx_bytes_per_sec == null || proc.net_tx_bytes_per_sec == null
+    ? undefined
+    : proc.net_rx_bytes_per_sec + proc.net_tx_bytes_per_sec;
+
+// Combined read + write rate to storage
+const diskRate = (proc: ProcessInfo): number | undefined =>
+  proc.disk_read_bytes_per_sec == null || proc.disk_write_bytes_per_sec == null
+    ? undefined
+    : proc.disk_read_bytes_per_sec + proc.disk_write_bytes_per_sec;
+
+const formatNetRate = (bytesPerSec?: number): string => {
+  if (bytesPerSec === undefined) return '—';
+  if (bytesPerSec < 1024) return `${bytesPerSec.toFixed(0)} B/s`;
+  if (bytesPerSec < 1024 * 1024) return `${(bytesPerSec / 1024).toFixed(1)} KB/s`;
+  return `${(bytesPerSec / 1024 / 1024).toFixed(1)} MB/s`;
+};
+
 // Inline SVG gear icon
 const GearIcon = () => (
   <svg className="process-icon" viewBox="0 0 24 24" fill="none">
@@ -44,8 +131,10 @@ type GroupedProcesses = {
   exe?: string;
   icon?: string | null;
   processes: ProcessInfo[];
-  totalMemory: number;
+  totalMemory: number; // bytes
   totalCpu: number;
+  totalNet?: number; // bytes/sec
+  totalDisk?: number; // bytes/sec
 };
 
 function groupProcesses(processes: ProcessInfo[]): GroupedProcesses[] {
@@ -63,8 +152,12 @@ function groupProcesses(processes: ProcessInfo[]): GroupedProcesses[] {
       };
     }
     groups[key].processes.push(proc);
-    groups[key].totalMemory += proc.memory;
+    groups[key].totalMemory += attributedMemory(proc);
     groups[key].totalCpu += proc.cpu;
+    const net = netRate(proc);
+    if (net !== undefined) groups[key].totalNet = (groups[key].totalNet ?? 0) + net;
+    const disk = diskRate(proc);
+    if (disk !== undefined) groups[key].totalDisk = (groups[key].totalDisk ?? 0) + disk;
   }
   // Sort groups by total RAM usage descending
   return Object.values(groups).sort((a, b) => b.totalMemory - a.totalMemory);
@@ -75,7 +168,7 @@ const Processes: React.FC = () => {
   const [activeTab, setActiveTab] = useState<'all' | 'top'>('all');
   const [sortColumn, setSortColumn] = useState<SortColumn>("cpu");
   const [sortDirection, setSortDirection] = useState<SortDirection>("desc");
-  const [totalMemory, setTotalMemory] = useState<number>(1); // KB
+  const [totalMemory, setTotalMemory] = useState<number>(1); // bytes
   const [contextMenu, setContextMenu] = useState<{ x: number; y: number; pid: number } | null>(null);
   const [confirmEnd, setConfirmEnd] = useState<{ pid: number; name: string } | null>(null);
   const [highlightedPid, setHighlightedPid] = useState<number | null>(null);
@@ -83,6 +176,9 @@ const Processes: React.FC = () => {
   const [expandedGroup, setExpandedGroup] = useState<string | null>(null);
   const [groupContextMenu, setGroupContextMenu] = useState<{ x: number; y: number; groupKey: string } | null>(null);
   const [confirmEndGroup, setConfirmEndGroup] = useState<{ groupKey: string; name: string } | null>(null);
+  const [details, setDetails] = useState<ProcessDetails | null>(null);
+  const [icons, setIcons] = useState<Record<string, string | null>>({}); // base64 PNG by cache key
+  const requestedIcons = useRef(new Set<string>());
 
   useEffect(() => {
     const fetchProcesses = async () => {
@@ -106,6 +202,21 @@ const Processes: React.FC = () => {
     return () => clearInterval(interval);
   }, [contextMenu, confirmEnd, groupContextMenu, confirmEndGroup]);
 
+  // Icons arrive separately from the process list, once per cache key
+  useEffect(() => {
+    for (const proc of processes) {
+      const key = proc.icon;
+      if (!key || !proc.exe || requestedIcons.current.has(key)) continue;
+      requestedIcons.current.add(key);
+      invoke<string | null>("fetch_process_icon", { exe: proc.exe })
+        .then((icon) => setIcons((prev) => ({ ...prev, [key]: icon })))
+        .catch((err) => console.error('Failed to fetch icon:', err));
+    }
+  }, [processes]);
+
+  const iconSrc = (key?: string | null) =>
+    key && icons[key] ? `data:image/png;base64,${icons[key]}` : undefined;
+
   // RAM % calculation
 
 
@@ -137,17 +248,37 @@ const Processes: React.FC = () => {
     setContextMenu(null);
   };
 
+  const handleDetailsClick = async (pid: number) => {
+    setContextMenu(null);
+    setHighlightedPid(null);
+    try {
+      setDetails(await invoke<ProcessDetails>("fetch_process_details", { pid }));
+    } catch (err) {
+      alert("Failed to read process details: " + err);
+    }
+  };
+
+  const handleControlClick = async (pid: number, command: string, args: Record<string, unknown> = {}) => {
+    setContextMenu(null);
+    setHighlightedPid(null);
+    try {
+      await invoke(command, { target: { pid }, ...args });
+    } catch (err) {
+      alert(`Failed to update process ${pid}: ${errorMessage(err)}`);
+    }
+  };
+
   const handleConfirmEnd = async () => {
     if (confirmEnd) {
       try {
-        await invoke("end_process", { pid: confirmEnd.pid });
-        setLastEnded(confirmEnd.name);
-      } catch (err) {
-        if (typeof err === "string" && err.includes("not found")) {
-          setLastEnded(confirmEnd.name); // treat as success
+        const result = await invoke<TerminationResult>("end_process", { pid: confirmEnd.pid });
+        if (result.outcome === "terminated" || result.outcome === "killed" || result.outcome === "not_found") {
+          setLastEnded(`${confirmEnd.name} (${describeOutcome(result.outcome)})`);
         } else {
-          alert("Failed to end process: " + err);
+          alert(`Failed to end ${confirmEnd.name}: ${describeOutcome(result.outcome)}`);
         }
+      } catch (err) {
+        alert("Failed to end process: " + errorMessage(err));
       }
       setConfirmEnd(null);
     }
@@ -177,10 +308,22 @@ const Processes: React.FC = () => {
       const group = grouped.find(g => (g.name + (g.exe || "")) === confirmEndGroup.groupKey);
       if (group) {
         try {
-          for (const proc of group.processes) {
-            await invoke("end_process", { pid: proc.pid });
+          // Protected processes reject; the rest of the group is still ended
+          const results = await Promise.all(
+            group.processes.map(proc =>
+              invoke<TerminationResult>("end_process", { pid: proc.pid })
+                .then(r => ({ pid: r.pid, failure: r.outcome === "permission_denied" || r.outcome === "still_running"
+                  ? describeOutcome(r.outcome) : null }))
+                .catch(err => ({ pid: proc.pid, failure: errorMessage(err) }))
+            )
+          );
+          const failed = results.filter(r => r.failure !== null);
+          if (failed.length === 0) {
+            setLastEnded(group.name + " (all processes)");
+          } else {
+            alert(`${failed.length} of ${results.length} ${group.name} processes could not be ended: `
+              + failed.map(r => `${r.pid} ${r.failure}`).join(", "));
           }
-          setLastEnded(group.name + " (all processes)");
         } catch (err) {
           alert("Failed to end all processes: " + err);
         }
@@ -235,6 +378,16 @@ const Processes: React.FC = () => {
           ? a.totalCpu - b.totalCpu
           : b.totalCpu - a.totalCpu;
       }
+      if (sortColumn === "network") {
+        return sortDirection === "asc"
+          ? (a.totalNet ?? 0) - (b.totalNet ?? 0)
+          : (b.totalNet ?? 0) - (a.totalNet ?? 0);
+      }
+      if (sortColumn === "disk") {
+        return sortDirection === "asc"
+          ? (a.totalDisk ?? 0) - (b.totalDisk ?? 0)
+          : (b.totalDisk ?? 0) - (a.totalDisk ?? 0);
+      }
       // Default: sort by name
       return sortDirection === "asc"
         ? a.name.localeCompare(b.name)
@@ -256,7 +409,7 @@ const Processes: React.FC = () => {
   // Memoize resource summary statistics
   const resourceStats = useMemo(() => {
     const totalCpuUsage = processes.reduce((sum, p) => sum + p.cpu, 0);
-    const totalRamUsage = processes.reduce((sum, p) => sum + p.memory, 0); // in KB
+    const totalRamUsage = processes.reduce((sum, p) => sum + attributedMemory(p), 0); // bytes
     const processCount = processes.length;
     const ramPercentage = (totalRamUsage / totalMemory) * 100;
     
@@ -267,8 +420,8 @@ const Processes: React.FC = () => {
     return {
       totalCpu: totalCpuUsage.toFixed(1),
       totalCpuRaw: totalCpuUsage,
-      totalRam: (totalRamUsage / 1024).toFixed(1), // Convert to MB
-      totalRamGB: (totalRamUsage / 1024 / 1024).toFixed(2), // Convert to GB
+      totalRam: toMB(totalRamUsage).toFixed(1),
+      totalRamGB: (totalRamUsage / 1024 / 1024 / 1024).toFixed(2),
       ramPercentage: ramPercentage.toFixed(1),
       ramPercentageRaw: ramPercentage,
       processCount,
@@ -416,8 +569,8 @@ const Processes: React.FC = () => {
                 <div key={idx} className="top-app-item">
                   <div className="top-app-info">
                     <span className="top-app-rank">#{idx + 1}</span>
-                    {group.icon ? (
-                      <img src={`data:image/png;base64,${group.icon}`} alt="icon" className="top-app-icon" />
+                    {iconSrc(group.icon) ? (
+                      <img src={iconSrc(group.icon)} alt="icon" className="top-app-icon" />
                     ) : (
                       <GearIcon />
                     )}
@@ -439,8 +592,8 @@ const Processes: React.FC = () => {
                 <div key={idx} className="top-app-item">
                   <div className="top-app-info">
                     <span className="top-app-rank">#{idx + 1}</span>
-                    {group.icon ? (
-                      <img src={`data:image/png;base64,${group.icon}`} alt="icon" className="top-app-icon" />
+                    {iconSrc(group.icon) ? (
+                      <img src={iconSrc(group.icon)} alt="icon" className="top-app-icon" />
                     ) : (
                       <GearIcon />
                     )}
@@ -449,7 +602,7 @@ const Processes: React.FC = () => {
                       <span className="top-app-runtime">{formatRuntime(group.processes[0]?.runtime)}</span>
                     </div>
                   </div>
-                  <span className="top-app-value">{(group.totalMemory / 1024).toFixed(1)} MB</span>
+                  <span className="top-app-value">{toMB(group.totalMemory).toFixed(1)} MB</span>
                 </div>
               ))}
             </div>
@@ -474,6 +627,12 @@ const Processes: React.FC = () => {
               <th onClick={() => handleSort("memory")}>
                 RAM (MB) {getArrow("memory")}
               </th>
+              <th onClick={() => handleSort("network")}>
+                Network {getArrow("network")}
+              </th>
+              <th onClick={() => handleSort("disk")}>
+                Disk {getArrow("disk")}
+              </th>
             </tr>
           </thead>
           <tbody>
@@ -488,9 +647,9 @@ const Processes: React.FC = () => {
                     style={{ cursor: "pointer" }}
                   >
                     <td>
-                      {group.icon ? (
+                      {iconSrc(group.icon) ? (
                         <img
-                          src={`data:image/png;base64,${group.icon}`}
+                          src={iconSrc(group.icon)}
                           alt="icon"
                           className="process-icon"
                         />
@@ -509,8 +668,10 @@ const Processes: React.FC = () => {
                     <td>{group.totalCpu.toFixed(1)}</td>
                     <td>
                       {/* Only show MB, remove % for RAM */}
-                      {(group.totalMemory / 1024).toFixed(1)} MB
+                      {toMB(group.totalMemory).toFixed(1)} MB
                     </td>
+                    <td>{formatNetRate(group.totalNet)}</td>
+                    <td>{formatNetRate(group.totalDisk)}</td>
                   </tr>
                   {expandedGroup === groupKey && group.processes.map(proc => (
                     <tr
@@ -519,11 +680,21 @@ const Processes: React.FC = () => {
                       onContextMenu={e => handleContextMenu(e, proc.pid)}
                     >
                       <td></td>
-                      <td style={{ paddingLeft: 32 }}>{proc.name} (PID: {proc.pid})</td>
+                      <td style={{ paddingLeft: 32 }}>
+                        {proc.name} (PID: {proc.pid})
+                        {(proc.user || proc.state || proc.unit || proc.container_id) && (
+                          <span style={{ color: "#888", marginLeft: 8 }}>
+                            {[proc.user, proc.state, proc.container_id ? `container ${proc.container_id.slice(0, 12)}` : proc.unit]
+                              .filter(Boolean).join(" · ")}
+                          </span>
+                        )}
+                      </td>
                       <td>{proc.cpu.toFixed(1)}</td>
-                      <td>
-                        {(proc.memory / 1024).toFixed(1)} MB
+                      <td title={describeMemory(proc)}>
+                        {toMB(attributedMemory(proc)).toFixed(1)} MB
                       </td>
+                      <td>{formatNetRate(netRate(proc))}</td>
+                      <td>{formatNetRate(diskRate(proc))}</td>
                     </tr>
                   ))}
                 </React.Fragment>
@@ -566,9 +737,71 @@ const Processes: React.FC = () => {
           style={{ top: contextMenu.y, left: contextMenu.x, position: "fixed", zIndex: 1000 }}
           onMouseLeave={handleCloseMenu}
         >
+          <button onClick={() => handleDetailsClick(contextMenu.pid)}>Details</button>
+          <button onClick={() => handleControlClick(contextMenu.pid, "suspend_process")}>Suspend</button>
+          <button onClick={() => handleControlClick(contextMenu.pid, "resume_process")}>Resume</button>
+          <button onClick={() => handleControlClick(contextMenu.pid, "set_process_priority", { nice: 10 })}>Lower Priority</button>
           <button onClick={() => handleEndTaskClick(contextMenu.pid)}>End Task</button>
         </div>
       )}
+      {details && (
+        <div className="process-details">
+          <div className="process-details-header">
+            <h3>{details.name} <span style={{ color: "#888" }}>(PID: {details.pid})</span></h3>
+            <button onClick={() => setDetails(null)}>✕</button>
+          </div>
+          <table>
+            <tbody>
+              <tr><td>Command</td><td>{details.cmdline.join(" ") || "—"}</td></tr>
+              <tr><td>Executable</td><td>{details.exe ?? "—"}</td></tr>
+              <tr><td>Working dir</td><td>{details.cwd ?? "—"}</td></tr>
+              <tr><td>Cgroup</td><td>{details.cgroups.map(c => c.path).join(", ") || "—"}</td></tr>
+              <tr>
+                <td>Memory maps</td>
+                <td>
+                  {details.maps
+                    ? `${details.maps.regions} regions, ${(details.maps.total_kb / 1024).toFixed(1)} MB virtual, ${details.maps.mapped_files.length} files`
+                    : "—"}
+                </td>
+              </tr>
+              <tr>
+                <td>Open files</td>
+                <td>
+                  {details.fds
+                    ? Object.entries(details.fds.reduce((acc: { [kind: string]: number }, f) => {
+                        acc[f.kind] = (acc[f.kind] ?? 0) + 1;
+                        return acc;
+                      }, {})).map(([kind, n]) => `${n} ${kind}`).join(", ") || "none"
+                    : "—"}
+                </td>
+              </tr>
+              <tr>
+                <td>Disk I/O</td>
+                <td>
+                  {details.io
+                    ? `${(details.io.read_bytes / 1024 / 1024).toFixed(1)} MB read, ${(details.io.write_bytes / 1024 / 1024).toFixed(1)} MB written`
+                    : "—"}
+                </td>
+              </tr>
+              <tr><td>Namespaces</td><td>{details.namespaces.map(n => n.kind).join(", ") || "—"}</td></tr>
+              <tr><td>Environment</td><td>{details.environment ? `${Object.keys(details.environment).length} variables` : "—"}</td></tr>
+            </tbody>
+          </table>
+          <h4>Threads ({details.threads.length})</h4>
+          <table>
+            <tbody>
+              {[...details.threads].sort((a, b) => b.cpu - a.cpu).slice(0, 10).map(t => (
+                <tr key={t.tid}>
+                  <td>{t.tid}</td>
+                  <td>{t.name}</td>
+                  <td>{t.state}</td>
+                  <td>{t.cpu.toFixed(1)}%</td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      )}
       {confirmEnd && (
         <div className="context-menu"
           style={{